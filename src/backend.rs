@@ -0,0 +1,200 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use rmcp::ErrorData as McpError;
+use serde_json::json;
+
+use crate::{
+    claude::ClaudeClient,
+    config::LlmConfig,
+    ollama::OllamaClient,
+    openai::OpenAiClient,
+    prompts::{CHANGES_PROMPT, SCAFFOLD_PROMPT, SYSTEM_PROMPT},
+};
+
+/// What a completion is for; decides the system prompt and which model runs it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Task {
+    Review,
+    /// Review of the hunks from recent saves rather than a whole file
+    ReviewChanges,
+    Scaffold,
+}
+
+impl Task {
+    pub fn system_prompt(self) -> &'static str {
+        match self {
+            Task::Review => SYSTEM_PROMPT,
+            Task::ReviewChanges => CHANGES_PROMPT,
+            Task::Scaffold => SCAFFOLD_PROMPT,
+        }
+    }
+}
+
+/// Why a call to an LLM backend failed, classified so the MCP host can tell
+/// the user whether to fix their key, wait, or shorten the request.
+#[derive(Debug, thiserror::Error)]
+pub enum LlmError {
+    #[error("authentication failed ({}): {message}", status.as_u16())]
+    Auth { status: StatusCode, message: String },
+    #[error("rate limited{}: {message}", fmt_retry_after(*retry_after))]
+    RateLimited {
+        retry_after: Option<Duration>,
+        message: String,
+    },
+    #[error(
+        "overloaded ({}){}: {message}",
+        status.as_u16(),
+        fmt_retry_after(*retry_after)
+    )]
+    Overloaded {
+        status: StatusCode,
+        retry_after: Option<Duration>,
+        message: String,
+    },
+    #[error("server error ({}): {message}", status.as_u16())]
+    Server { status: StatusCode, message: String },
+    #[error("bad request ({}): {message}", status.as_u16())]
+    BadRequest { status: StatusCode, message: String },
+    #[error("no response within {}s", .0.as_secs())]
+    Timeout(Duration),
+    #[error("network error: {0}")]
+    Network(reqwest::Error),
+    #[error("invalid response: {0}")]
+    InvalidResponse(String),
+    #[error("sampling request failed: {0}")]
+    Sampling(String),
+}
+
+impl From<reqwest::Error> for LlmError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_decode() {
+            LlmError::InvalidResponse(e.to_string())
+        } else {
+            LlmError::Network(e)
+        }
+    }
+}
+
+fn fmt_retry_after(retry_after: Option<Duration>) -> String {
+    retry_after
+        .map(|d| format!(", retry after {}s", d.as_secs()))
+        .unwrap_or_default()
+}
+
+impl LlmError {
+    fn kind(&self) -> &'static str {
+        match self {
+            LlmError::Auth { .. } => "auth",
+            LlmError::RateLimited { .. } => "rate_limited",
+            LlmError::Overloaded { .. } => "overloaded",
+            LlmError::Server { .. } => "server",
+            LlmError::BadRequest { .. } => "bad_request",
+            LlmError::Timeout(_) => "timeout",
+            LlmError::Network(_) => "network",
+            LlmError::InvalidResponse(_) => "invalid_response",
+            LlmError::Sampling(_) => "sampling",
+        }
+    }
+
+    // into_mcp_error - this keeps the classification in `data` so hosts can
+    // branch on `kind` instead of parsing the message
+    pub fn into_mcp_error(self, backend: &str) -> McpError {
+        let status = match &self {
+            LlmError::Auth { status, .. }
+            | LlmError::Overloaded { status, .. }
+            | LlmError::Server { status, .. }
+            | LlmError::BadRequest { status, .. } => Some(status.as_u16()),
+            LlmError::RateLimited { .. } => Some(429),
+            _ => None,
+        };
+        let retry_after_secs = match &self {
+            LlmError::RateLimited { retry_after, .. }
+            | LlmError::Overloaded { retry_after, .. } => retry_after.map(|d| d.as_secs()),
+            _ => None,
+        };
+        let data = json!({
+            "backend": backend,
+            "kind": self.kind(),
+            "status": status,
+            "retry_after_secs": retry_after_secs,
+        });
+        let message = format!("{backend} backend {self}");
+
+        match self {
+            LlmError::BadRequest { .. } => McpError::invalid_request(message, Some(data)),
+            _ => McpError::internal_error(message, Some(data)),
+        }
+    }
+}
+
+/// Text produced by a backend along with what it cost to produce.
+#[derive(Debug, Clone)]
+pub struct Completion {
+    pub text: String,
+    /// Model that actually served the request, as reported by the backend
+    pub model: String,
+    pub usage: Usage,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Usage {
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+}
+
+/// Callback receiving each chunk of generated text during a streaming call.
+pub type DeltaFn = dyn for<'s> Fn(&'s str) + Send + Sync;
+
+/// An LLM that can review code and plan scaffolds.
+///
+/// Implementations only need to provide `complete`; the review and scaffold
+/// entry points are built on top of it so every backend uses the same prompts.
+#[async_trait]
+pub trait ReviewBackend: Send + Sync {
+    /// Short human-readable name used in logs and error messages
+    fn name(&self) -> &'static str;
+
+    /// Send the task's system prompt plus `prompt` and return the text reply
+    async fn complete(&self, task: Task, prompt: &str) -> Result<Completion, LlmError>;
+
+    /// Like `complete`, but calls `on_delta` with each chunk of text as it is
+    /// generated. Backends without streaming support deliver one final chunk.
+    async fn complete_streaming(
+        &self,
+        task: Task,
+        prompt: &str,
+        on_delta: &DeltaFn,
+    ) -> Result<Completion, LlmError> {
+        let completion = self.complete(task, prompt).await?;
+        on_delta(&completion.text);
+        Ok(completion)
+    }
+
+    async fn scaffold(&self, description: &str) -> Result<Completion, LlmError> {
+        self.complete(Task::Scaffold, description).await
+    }
+}
+
+// from_config - this picks a backend from `llm.backend`, falling back to
+// Anthropic when an API key is available and to no backend otherwise
+pub fn from_config(config: &LlmConfig) -> Result<Option<Arc<dyn ReviewBackend>>> {
+    let backend: Arc<dyn ReviewBackend> = match config.backend.as_deref() {
+        Some("anthropic") | None => match config.api_key_or_env("ANTHROPIC_API_KEY") {
+            Some(key) => Arc::new(ClaudeClient::new(key, config)),
+            None if config.backend.is_some() => {
+                bail!("the anthropic backend requires ANTHROPIC_API_KEY or llm.api_key")
+            }
+            None => return Ok(None),
+        },
+        Some("openai") => Arc::new(OpenAiClient::new(config)),
+        Some("ollama") => Arc::new(OllamaClient::new(config)),
+        Some(other) => {
+            bail!("unknown LLM backend `{other}` (expected anthropic, openai or ollama)")
+        }
+    };
+
+    Ok(Some(backend))
+}
@@ -0,0 +1,395 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+};
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::{
+    config::CargoCheckConfig,
+    manifest::{self, LockedVersions},
+    store::{DiagnosticRecord, TutorStore},
+};
+
+/// One compiler or clippy diagnostic from cargo's `--message-format=json`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub level: String,        // error, warning, ...
+    pub code: Option<String>, // E0382, unused_variables, clippy::needless_return, ...
+    pub message: String,
+    pub file_path: Option<String>, // absolute, to match the watcher's paths
+    pub line_start: Option<i64>,
+    pub column_start: Option<i64>,
+    pub line_end: Option<i64>,
+    pub rendered: Option<String>,
+}
+
+impl Diagnostic {
+    // into_record - `source` is the tool that reported the diagnostic rather
+    // than the command that ran: clippy passes the compiler's own errors and
+    // warnings through, and those stay `check` so filtering by source works
+    pub fn into_record(
+        self,
+        command: &str,
+        change_id: Option<String>,
+        session_id: Option<i64>,
+        created_at: chrono::DateTime<chrono::Utc>,
+    ) -> DiagnosticRecord {
+        let is_lint = self
+            .code
+            .as_deref()
+            .is_some_and(|c| c.starts_with("clippy::"));
+        let source = if command == "clippy" && !is_lint {
+            "check"
+        } else {
+            command
+        };
+        DiagnosticRecord {
+            change_id,
+            session_id,
+            source: source.to_string(),
+            run_id: None,
+            level: self.level,
+            code: self.code,
+            message: self.message,
+            file_path: self.file_path,
+            line_start: self.line_start,
+            column_start: self.column_start,
+            line_end: self.line_end,
+            rendered: self.rendered,
+            created_at,
+        }
+    }
+}
+
+// the slice of cargo's JSON messages we read
+#[derive(Deserialize)]
+struct CargoMessage {
+    reason: String,
+    message: Option<CompilerMessage>,
+}
+
+#[derive(Deserialize)]
+struct CompilerMessage {
+    message: String,
+    level: String,
+    code: Option<CompilerCode>,
+    #[serde(default)]
+    spans: Vec<Span>,
+    rendered: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CompilerCode {
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct Span {
+    file_name: String,
+    line_start: i64,
+    line_end: i64,
+    column_start: i64,
+    is_primary: bool,
+}
+
+/// A save that should trigger a check, and where its diagnostics belong.
+pub struct CheckRequest {
+    pub change_id: String,
+    pub session_id: Option<i64>,
+}
+
+pub struct CargoChecker {}
+
+impl CargoChecker {
+    // spawn - this starts the background checker for the project at `root`.
+    // Requests that queue up while a check runs are folded into one follow-up
+    // check, credited to the newest save
+    pub fn spawn(
+        root: PathBuf,
+        config: CargoCheckConfig,
+        store: Arc<Mutex<TutorStore>>,
+    ) -> Sender<CheckRequest> {
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || run_checks(&root, &config, &store, rx));
+        tx
+    }
+}
+
+fn run_checks(
+    root: &Path,
+    config: &CargoCheckConfig,
+    store: &Mutex<TutorStore>,
+    rx: Receiver<CheckRequest>,
+) {
+    while let Ok(mut request) = rx.recv() {
+        while let Ok(newer) = rx.try_recv() {
+            request = newer;
+        }
+
+        let (source, result) = if config.clippy {
+            ("clippy", clippy(root, &config.args))
+        } else {
+            ("check", check(root, &config.args))
+        };
+        let diagnostics = match result {
+            Ok(diagnostics) => diagnostics,
+            Err(e) => {
+                tracing::warn!("cargo {source} failed: {e}");
+                continue;
+            }
+        };
+        tracing::debug!(
+            change_id = %request.change_id,
+            count = diagnostics.len(),
+            "cargo {source} finished"
+        );
+
+        let ran_at = chrono::Utc::now();
+        let records: Vec<DiagnosticRecord> = diagnostics
+            .into_iter()
+            .map(|d| {
+                d.into_record(
+                    source,
+                    Some(request.change_id.clone()),
+                    request.session_id,
+                    ran_at,
+                )
+            })
+            .collect();
+
+        // the run is kept even when clean, so a code that stops showing up
+        // can be told apart from one that was never checked. Its source is
+        // the command that ran, which decides what it could have caught
+        if let Err(e) = store.lock().expect("store lock poisoned").save_check_run(
+            &request.change_id,
+            request.session_id,
+            source,
+            ran_at,
+            &records,
+        ) {
+            tracing::error!("failed to save check run: {e}");
+        }
+    }
+}
+
+// check - this runs `cargo check` in `root` and returns what the compiler reported
+pub fn check(root: &Path, args: &[String]) -> Result<Vec<Diagnostic>> {
+    run("check", root, args)
+}
+
+// clippy - this runs `cargo clippy` in `root` and returns its lints along
+// with the compiler's own warnings and errors
+pub fn clippy(root: &Path, args: &[String]) -> Result<Vec<Diagnostic>> {
+    run("clippy", root, args)
+}
+
+// run - a failing build still yields diagnostics; only a cargo that couldn't
+// run at all (missing, no manifest, clippy not installed) is an error
+fn run(subcommand: &str, root: &Path, args: &[String]) -> Result<Vec<Diagnostic>> {
+    let output = Command::new("cargo")
+        .arg(subcommand)
+        .arg("--message-format=json")
+        .args(args)
+        .current_dir(root)
+        .output()
+        .with_context(|| format!("failed to run cargo {subcommand}"))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() && stdout.trim().is_empty() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    // span paths are relative to the workspace root, which can sit above `root`
+    Ok(parse_messages(&workspace_root(root), &stdout))
+}
+
+fn workspace_root(dir: &Path) -> PathBuf {
+    Command::new("cargo")
+        .args(["locate-project", "--workspace", "--message-format", "plain"])
+        .current_dir(dir)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .and_then(|o| String::from_utf8(o.stdout).ok())
+        .and_then(|manifest| Some(Path::new(manifest.trim()).parent()?.to_path_buf()))
+        .unwrap_or_else(|| dir.to_path_buf())
+}
+
+// the slice of `cargo metadata` we read
+#[derive(Deserialize)]
+struct Metadata {
+    target_directory: PathBuf,
+    packages: Vec<MetadataPackage>,
+}
+
+#[derive(Deserialize)]
+struct MetadataPackage {
+    name: String,
+    manifest_path: PathBuf,
+    targets: Vec<MetadataTarget>,
+}
+
+#[derive(Deserialize)]
+struct MetadataTarget {
+    name: String,
+    kind: Vec<String>,
+}
+
+fn metadata(root: &Path) -> Result<Metadata> {
+    let output = Command::new("cargo")
+        .args(["metadata", "--no-deps", "--format-version", "1"])
+        .current_dir(root)
+        .output()
+        .context("failed to run cargo metadata")?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    serde_json::from_slice(&output.stdout).context("failed to parse cargo metadata")
+}
+
+// target_dir - this asks cargo where build output goes, so CARGO_TARGET_DIR
+// and `build.target-dir` are honored
+pub fn target_dir(root: &Path) -> PathBuf {
+    metadata(root)
+        .map(|m| m.target_directory)
+        .unwrap_or_else(|_| workspace_root(root).join("target"))
+}
+
+// workspace_manifests - this lists the workspace's own packages and their
+// manifests, or just `root`'s manifest when cargo can't say
+pub fn workspace_manifests(root: &Path) -> Vec<(String, PathBuf)> {
+    match metadata(root) {
+        Ok(metadata) if !metadata.packages.is_empty() => metadata
+            .packages
+            .into_iter()
+            .map(|p| (p.name, p.manifest_path))
+            .collect(),
+        _ => vec![(String::new(), root.join("Cargo.toml"))],
+    }
+}
+
+// locked_versions - this reads the workspace's Cargo.lock, if it has one
+pub fn locked_versions(root: &Path) -> LockedVersions {
+    let lockfile = workspace_root(root).join("Cargo.lock");
+    let Ok(text) = std::fs::read_to_string(&lockfile) else {
+        return LockedVersions::default();
+    };
+    manifest::parse_locked_versions(&text).unwrap_or_else(|e| {
+        tracing::warn!("failed to read {}: {e}", lockfile.display());
+        LockedVersions::default()
+    })
+}
+
+// rustdoc_json - this runs `cargo +nightly rustdoc` with JSON output for
+// `package` (the crate at `root` when unset) and returns the file it wrote
+pub fn rustdoc_json(root: &Path, package: Option<&str>) -> Result<PathBuf> {
+    let metadata = metadata(root)?;
+    // cargo reports canonical paths, which `root` isn't under a symlinked checkout
+    let manifest_path = root.join("Cargo.toml");
+    let manifest_path = manifest_path.canonicalize().unwrap_or(manifest_path);
+    let lib_name = match package {
+        Some(package) => package.replace('-', "_"),
+        None => metadata
+            .packages
+            .iter()
+            .find(|p| p.manifest_path == manifest_path)
+            .and_then(|p| {
+                p.targets
+                    .iter()
+                    .find(|t| t.kind.iter().any(|k| k.ends_with("lib")))
+            })
+            .map(|t| t.name.replace('-', "_"))
+            .context("rustdoc JSON needs a library target; name a dependency with `crate_name`")?,
+    };
+
+    let mut command = Command::new("cargo");
+    command.args(["+nightly", "rustdoc", "--lib"]);
+    if let Some(package) = package {
+        command.args(["-p", package]);
+    }
+    let output = command
+        .args(["--", "-Z", "unstable-options", "--output-format", "json"])
+        .current_dir(root)
+        .output()
+        .context("failed to run cargo +nightly rustdoc")?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    Ok(metadata
+        .target_directory
+        .join("doc")
+        .join(format!("{lib_name}.json")))
+}
+
+// crate_root - this finds the directory of the Cargo.toml that owns `path`
+pub fn crate_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|dir| dir.join("Cargo.toml").is_file())
+        .map(Path::to_path_buf)
+}
+
+// parse_messages - this pulls compiler diagnostics out of cargo's JSON lines,
+// dropping "aborting due to ..." style summaries, `--explain` hints and the
+// duplicates from building the same file for several targets
+pub fn parse_messages(root: &Path, output: &str) -> Vec<Diagnostic> {
+    let mut seen = HashSet::new();
+    let mut diagnostics = Vec::new();
+
+    for line in output.lines() {
+        let Ok(CargoMessage {
+            reason,
+            message: Some(message),
+        }) = serde_json::from_str(line)
+        else {
+            continue;
+        };
+        if reason != "compiler-message" || is_summary(&message) {
+            continue;
+        }
+
+        let span = message
+            .spans
+            .iter()
+            .find(|s| s.is_primary)
+            .or(message.spans.first());
+        let diagnostic = Diagnostic {
+            level: message.level,
+            code: message.code.map(|c| c.code),
+            message: message.message,
+            file_path: span.map(|s| root.join(&s.file_name).to_string_lossy().into_owned()),
+            line_start: span.map(|s| s.line_start),
+            column_start: span.map(|s| s.column_start),
+            line_end: span.map(|s| s.line_end),
+            rendered: message.rendered,
+        };
+
+        let key = (
+            diagnostic.level.clone(),
+            diagnostic.message.clone(),
+            diagnostic.file_path.clone(),
+            diagnostic.line_start,
+        );
+        if seen.insert(key) {
+            diagnostics.push(diagnostic);
+        }
+    }
+
+    diagnostics
+}
+
+fn is_summary(message: &CompilerMessage) -> bool {
+    // "For more information about this error, try `rustc --explain ...`"
+    message.level == "failure-note"
+        || message.spans.is_empty()
+            && (message.message.starts_with("aborting due to")
+                || message.message.ends_with("warning emitted")
+                || message.message.ends_with("warnings emitted"))
+}
@@ -0,0 +1,117 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    history::{self, FileVersion},
+    store::{describe_kind, FileChangeRecord, TutorStore},
+};
+
+// build_changes_prompt - this renders stored hunks as a compact diff for the
+// reviewer, each with `context_lines` of the file as that save left it: the
+// disk when the save is the file's current state, else rebuilt from history
+pub fn build_changes_prompt(
+    store: &TutorStore,
+    changes: &[FileChangeRecord],
+    context_lines: usize,
+) -> String {
+    let mut by_file: BTreeMap<&str, Vec<&FileChangeRecord>> = BTreeMap::new();
+    for change in changes {
+        by_file.entry(&change.file_path).or_default().push(change);
+    }
+
+    let mut out = String::new();
+    for (file_path, mut hunks) in by_file {
+        hunks.sort_by_key(|c| (c.changed_at, c.hunk_idx));
+        let current = std::fs::read_to_string(file_path).ok();
+        let current_hash = current.as_deref().map(history::content_hash);
+        let mut after_save: HashMap<&str, Option<(bool, String)>> = HashMap::new();
+
+        let kind = hunks
+            .last()
+            .map(|h| describe_kind(&h.kind, h.old_path.as_deref()))
+            .unwrap_or_default();
+        out.push_str(&format!("## `{file_path}`{kind}\n\n"));
+        let mut saves: HashMap<&str, usize> = HashMap::new();
+        for hunk in hunks {
+            let next = saves.len() + 1;
+            let save = *saves.entry(&hunk.change_id).or_insert(next);
+            // a pure deletion leaves no lines behind to give a range for
+            let lines = if hunk.new_count == 0 {
+                format!("deletion at line {}", hunk.new_start + 1)
+            } else {
+                format!(
+                    "lines {}-{}",
+                    hunk.new_start + 1,
+                    hunk.new_start + hunk.new_count
+                )
+            };
+            out.push_str(&format!(
+                "### Save {save}, hunk {} — {lines} ({})\n\n```diff\n",
+                hunk.hunk_idx + 1,
+                hunk.changed_at.format("%Y-%m-%d %H:%M:%S")
+            ));
+            for line in hunk.before_lines.lines() {
+                out.push_str(&format!("-{line}\n"));
+            }
+            for line in hunk.after_lines.lines() {
+                out.push_str(&format!("+{line}\n"));
+            }
+            out.push_str("```\n\n");
+
+            let after = after_save.entry(&hunk.change_id).or_insert_with(|| {
+                if hunk.content_hash.is_some() && hunk.content_hash == current_hash {
+                    return current.clone().map(|c| (true, c));
+                }
+                match history::file_at(store, file_path, hunk.changed_at) {
+                    Ok(FileVersion::Present(contents)) => Some((false, contents)),
+                    _ => None,
+                }
+            });
+            if let Some((is_current, contents)) = after {
+                let lines: Vec<&str> = contents.lines().collect();
+                let start = (hunk.new_start as usize).saturating_sub(context_lines);
+                let end = (hunk.new_start as usize)
+                    .saturating_add(hunk.new_count as usize)
+                    .saturating_add(context_lines)
+                    .min(lines.len());
+                if start < end {
+                    if *is_current {
+                        out.push_str("Current code around this hunk:\n\n```rust\n");
+                    } else {
+                        out.push_str(&format!(
+                            "Code around this hunk after save {save}:\n\n```rust\n"
+                        ));
+                    }
+                    for (n, line) in lines[start..end].iter().enumerate() {
+                        out.push_str(&format!("{:>5} | {line}\n", start + n + 1));
+                    }
+                    out.push_str("```\n\n");
+                }
+            }
+        }
+    }
+
+    out
+}
+
+// reviewed_files - this pairs each changed file with the hash of the contents
+// a review of `changes` ends at: its newest save's hash, or what's on disk for
+// saves recorded before hashes were kept (nothing, once a file is deleted)
+pub fn reviewed_files(changes: &[FileChangeRecord]) -> Vec<(String, String)> {
+    let mut latest: BTreeMap<&str, &FileChangeRecord> = BTreeMap::new();
+    for change in changes {
+        let newest = latest.entry(&change.file_path).or_insert(change);
+        if (change.changed_at, change.hunk_idx) > (newest.changed_at, newest.hunk_idx) {
+            *newest = change;
+        }
+    }
+
+    latest
+        .into_iter()
+        .map(|(file_path, change)| {
+            let hash = change.content_hash.clone().unwrap_or_else(|| {
+                history::content_hash(&std::fs::read_to_string(file_path).unwrap_or_default())
+            });
+            (file_path.to_string(), hash)
+        })
+        .collect()
+}
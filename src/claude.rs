@@ -1,87 +1,54 @@
-use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 
-const MODEL: &str = "claude-sonnet-4-6";
-const MAX_TOKENS: u32 = 4096;
+use crate::{
+    backend::{Completion, DeltaFn, LlmError, ReviewBackend, Task, Usage},
+    config::{LlmConfig, ModelSettings},
+    retry::{send_with_retry, RetryPolicy},
+};
 
-pub const SCAFFOLD_PROMPT: &str = r#"You are an expert Rust tutor helping a student plan an implementation before they write code. Your goal is to teach architectural thinking — not to write the code for them.
-
-## Core rule
-
-**Do not write full implementations.** Use type signatures, short illustrative snippets (3-5 lines max), and prose to convey ideas. The student should do the implementation work themselves.
-
-## How to scaffold
-
-1. **Clarify the goal.** Restate what the student wants to build in your own words to confirm understanding.
-
-2. **Propose a module structure.** Name the files/modules and what each is responsible for. One or two sentences per module — no code.
-
-3. **Define the key types.** Show signatures only — struct fields and enum variants with a brief explanation of why each exists. No `impl` blocks.
-
-4. **Identify traits to implement.** Name the traits and explain *why* each one is useful here. A single line showing the trait bound is enough — no method bodies.
-
-5. **Suggest crates.** Name the crate, what it provides, and why it fits. One sentence each.
-
-6. **Lay out a build order.** Numbered steps the student can follow incrementally. Each step should be a goal ("implement X so that Y compiles"), not a code block.
-
-7. **Call out Rust-specific gotchas.** Ownership decisions, error handling strategy, lifetime considerations — in prose or with a minimal example only where words aren't enough.
-
-## Calibrate to skill level
-
-Infer experience from how the student describes the project. Lean toward more explanation for beginners, more brevity and advanced patterns for experienced developers. When in doubt, explain the *why* and let the student figure out the *how*."#;
-
-pub const SYSTEM_PROMPT: &str = r#"You are an expert Rust tutor helping a student improve their Rust skills. Your goal is to teach, not just review — explain the reasoning behind every suggestion so the student learns the underlying principles.
-
-## How to review
-
-1. **Start with what's done well.** Acknowledge good patterns and correct usage before diving into suggestions. This reinforces good habits.
-
-2. **Prioritize feedback by impact.** Lead with the most important issues for *this specific code* — don't mechanically walk through every category. A small utility function doesn't need a safety audit.
-
-3. **Explain the "why", not just the "what".** Don't just say "use iterators here" — explain *why* iterators are preferred in Rust (laziness, composability, avoiding index-out-of-bounds, borrow checker friendliness). Connect suggestions to Rust's ownership model, type system, or standard library design philosophy.
-
-4. **Provide concrete before-and-after code.** Show the original snippet alongside your suggested version so the student can compare.
-
-5. **Calibrate to skill level.** Infer the student's experience from their code. Beginners benefit from explaining `Option`/`Result` basics; experienced developers benefit from advanced patterns like `impl Into<T>`, newtype wrappers, or zero-cost abstractions.
-
-## What to look for
-
-- **Idiomatic Rust**: Patterns that could leverage iterators, pattern matching, `Option`/`Result` combinators, or standard library features more effectively.
-- **Common mistakes**: Unnecessary clones, `.unwrap()` in non-prototype code, improper error handling, missing derives, fighting the borrow checker.
-- **Performance**: Unnecessary allocations, inefficient data structures, missed opportunities for zero-copy or borrowing.
-- **Safety**: Any `unsafe` usage and whether it's justified.
-- **Style**: Naming conventions, module organization, and readability.
-
-## Learning resources
-
-When relevant, point the student to specific resources:
-- Clippy lint names (e.g., `clippy::needless_collect`) so they can enable them
-- Relevant chapters of The Rust Book (e.g., "Chapter 13: Iterators and Closures")
-- Rust by Example sections, Rustonomicon for unsafe topics, or std library docs for specific types"#;
+const DEFAULT_BASE_URL: &str = "https://api.anthropic.com";
+const DEFAULT_MODEL: &str = "claude-sonnet-4-6";
 
 #[derive(Clone)]
 pub struct ClaudeClient {
     client: reqwest::Client,
     api_key: String,
+    base_url: String,
+    models: ModelSettings,
+    retry: RetryPolicy,
 }
 
 #[derive(Serialize)]
-struct ApiRequest {
-    model: &'static str,
+struct ApiRequest<'a> {
+    model: &'a str,
     max_tokens: u32,
-    system: &'static str,
-    messages: Vec<Message>,
+    system: &'a str,
+    messages: Vec<Message<'a>>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
 }
 
 #[derive(Serialize)]
-struct Message {
+struct Message<'a> {
     role: &'static str,
-    content: String,
+    content: &'a str,
 }
 
 #[derive(Deserialize)]
 struct ApiResponse {
     content: Vec<ContentBlock>,
+    model: String,
+    usage: ApiUsage,
+}
+
+#[derive(Deserialize, Default)]
+struct ApiUsage {
+    #[serde(default)]
+    input_tokens: i64,
+    #[serde(default)]
+    output_tokens: i64,
 }
 
 #[derive(Deserialize)]
@@ -89,70 +56,222 @@ struct ContentBlock {
     text: String,
 }
 
+// StreamEvent - the subset of Messages API server-sent events we act on
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamEvent {
+    MessageStart {
+        message: StreamMessage,
+    },
+    ContentBlockDelta {
+        delta: StreamDelta,
+    },
+    MessageDelta {
+        #[serde(default)]
+        usage: ApiUsage,
+    },
+    Error {
+        error: StreamError,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct StreamMessage {
+    model: String,
+    #[serde(default)]
+    usage: ApiUsage,
+}
+
+#[derive(Deserialize)]
+struct StreamDelta {
+    #[serde(default)]
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct StreamError {
+    #[serde(rename = "type")]
+    kind: String,
+    message: String,
+}
+
+impl From<StreamError> for LlmError {
+    fn from(error: StreamError) -> Self {
+        let message = error.message;
+        match error.kind.as_str() {
+            "overloaded_error" => LlmError::Overloaded {
+                status: StatusCode::from_u16(529).expect("529 is a valid status code"),
+                retry_after: None,
+                message,
+            },
+            "rate_limit_error" => LlmError::RateLimited {
+                retry_after: None,
+                message,
+            },
+            "authentication_error" | "permission_error" => LlmError::Auth {
+                status: StatusCode::UNAUTHORIZED,
+                message,
+            },
+            "invalid_request_error" => LlmError::BadRequest {
+                status: StatusCode::BAD_REQUEST,
+                message,
+            },
+            _ => LlmError::Server {
+                status: StatusCode::INTERNAL_SERVER_ERROR,
+                message,
+            },
+        }
+    }
+}
+
 impl ClaudeClient {
-    pub fn new(api_key: String) -> Self {
+    pub fn new(api_key: String, config: &LlmConfig) -> Self {
         Self {
             client: reqwest::Client::new(),
             api_key,
+            base_url: config
+                .base_url_or_env("ANTHROPIC_BASE_URL")
+                .unwrap_or_else(|| DEFAULT_BASE_URL.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            models: config.models(DEFAULT_MODEL),
+            retry: RetryPolicy::from_config(config),
         }
     }
 
-    pub async fn scaffold(&self, description: &str) -> Result<String> {
-        let request = ApiRequest {
-            model: MODEL,
-            max_tokens: MAX_TOKENS,
-            system: SCAFFOLD_PROMPT,
+    fn request<'a>(&'a self, task: Task, prompt: &'a str, stream: bool) -> ApiRequest<'a> {
+        ApiRequest {
+            model: self.models.model_for(task),
+            max_tokens: self.models.max_tokens,
+            system: task.system_prompt(),
             messages: vec![Message {
                 role: "user",
-                content: description.to_string(),
+                content: prompt,
             }],
-        };
-
-        self.call_api(request).await
+            stream,
+        }
     }
 
-    pub async fn review(&self, code: &str) -> Result<String> {
-        let request = ApiRequest {
-            model: MODEL,
-            max_tokens: MAX_TOKENS,
-            system: SYSTEM_PROMPT,
-            messages: vec![Message {
-                role: "user",
-                content: code.to_string(),
-            }],
-        };
-
-        self.call_api(request).await
+    async fn send(&self, request: &ApiRequest<'_>) -> Result<reqwest::Response, LlmError> {
+        send_with_retry(&self.retry, || {
+            self.client
+                .post(format!("{}/v1/messages", self.base_url))
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", "2023-06-01")
+                .header("content-type", "application/json")
+                .json(request)
+        })
+        .await
     }
 
-    async fn call_api(&self, request: ApiRequest) -> Result<String> {
-        let response = self
-            .client
-            .post("https://api.anthropic.com/v1/messages")
-            .header("x-api-key", &self.api_key)
-            .header("anthropic-version", "2023-06-01")
-            .header("content-type", "application/json")
-            .json(&request)
-            .send()
-            .await
-            .context("failed to send request to Claude API")?;
+    async fn call_api(&self, request: ApiRequest<'_>) -> Result<Completion, LlmError> {
+        let response = self.send(&request).await?;
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            bail!("Claude API returned {status}: {body}");
-        }
+        let api_response: ApiResponse = response.json().await?;
 
-        let api_response: ApiResponse = response
-            .json()
-            .await
-            .context("failed to parse Claude API response")?;
-
-        api_response
+        let text = api_response
             .content
             .into_iter()
             .next()
             .map(|block| block.text)
-            .context("Claude API returned empty response")
+            .ok_or_else(|| {
+                LlmError::InvalidResponse("Claude API returned empty response".into())
+            })?;
+
+        Ok(Completion {
+            text,
+            model: api_response.model,
+            usage: api_response.usage.into(),
+        })
+    }
+
+    // stream_api - this reads the SSE body event by event, forwarding each
+    // text delta as it arrives and returning the concatenated text at the end
+    async fn stream_api(
+        &self,
+        request: ApiRequest<'_>,
+        on_delta: &DeltaFn,
+    ) -> Result<Completion, LlmError> {
+        let mut response = self.send(&request).await?;
+
+        let mut buffer = Vec::new();
+        let mut text = String::new();
+        let mut model = request.model.to_string();
+        let mut usage = Usage::default();
+        while let Some(chunk) = response.chunk().await? {
+            // proxies may end lines with \r\n; dropping the \r here also
+            // covers a pair split across chunks
+            buffer.extend(chunk.iter().filter(|&&b| b != b'\r'));
+
+            while let Some(end) = buffer.windows(2).position(|w| w == b"\n\n") {
+                let event: Vec<u8> = buffer.drain(..end + 2).collect();
+                let event = String::from_utf8_lossy(&event);
+
+                for data in event.lines().filter_map(|l| l.strip_prefix("data:")) {
+                    match serde_json::from_str::<StreamEvent>(data.trim()) {
+                        Ok(StreamEvent::MessageStart { message }) => {
+                            model = message.model;
+                            usage.input_tokens = message.usage.input_tokens;
+                            usage.output_tokens = message.usage.output_tokens;
+                        }
+                        // output_tokens in message_delta is cumulative
+                        Ok(StreamEvent::MessageDelta { usage: delta }) => {
+                            usage.output_tokens = delta.output_tokens;
+                        }
+                        Ok(StreamEvent::ContentBlockDelta { delta }) if !delta.text.is_empty() => {
+                            on_delta(&delta.text);
+                            text.push_str(&delta.text);
+                        }
+                        Ok(StreamEvent::Error { error }) => return Err(error.into()),
+                        Ok(_) => {}
+                        Err(e) => {
+                            return Err(LlmError::InvalidResponse(format!(
+                                "malformed stream event: {e}"
+                            )))
+                        }
+                    }
+                }
+            }
+        }
+
+        if text.is_empty() {
+            return Err(LlmError::InvalidResponse(
+                "Claude API returned empty response".into(),
+            ));
+        }
+
+        Ok(Completion { text, model, usage })
+    }
+}
+
+impl From<ApiUsage> for Usage {
+    fn from(usage: ApiUsage) -> Self {
+        Self {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+        }
+    }
+}
+
+#[async_trait]
+impl ReviewBackend for ClaudeClient {
+    fn name(&self) -> &'static str {
+        "anthropic"
+    }
+
+    async fn complete(&self, task: Task, prompt: &str) -> Result<Completion, LlmError> {
+        self.call_api(self.request(task, prompt, false)).await
+    }
+
+    async fn complete_streaming(
+        &self,
+        task: Task,
+        prompt: &str,
+        on_delta: &DeltaFn,
+    ) -> Result<Completion, LlmError> {
+        self.stream_api(self.request(task, prompt, true), on_delta)
+            .await
     }
 }
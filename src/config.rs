@@ -0,0 +1,253 @@
+use std::{collections::HashMap, num::NonZeroU32, path::PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::backend::Task;
+
+/// Server configuration, read from `config.toml` and overridden by env vars.
+///
+/// The file lives at `$RUST_TUTOR_CONFIG` when set, otherwise at
+/// `<config dir>/rust-tutor-mcp/config.toml`. A missing file is not an error.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub llm: LlmConfig,
+    pub watcher: WatcherConfig,
+    pub cargo_check: CargoCheckConfig,
+    /// Per-model prices used by `usage_report`, keyed by model name.
+    /// Entries here override the built-in Claude prices.
+    pub pricing: HashMap<String, ModelPrice>,
+}
+
+/// Which LLM to talk to and how.
+///
+/// ```toml
+/// [llm]
+/// backend = "anthropic"
+/// base_url = "http://localhost:8080"
+/// review_model = "claude-haiku-4-5"
+/// scaffold_model = "claude-sonnet-4-6"
+/// max_tokens = 4096
+/// max_retries = 3
+/// request_timeout_secs = 120
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LlmConfig {
+    /// `anthropic`, `openai` or `ollama`; unset means anthropic if a key is present
+    pub backend: Option<String>,
+    pub api_key: Option<String>,
+    /// Overrides the backend's default endpoint (proxies, mocks, on-prem servers)
+    pub base_url: Option<String>,
+    /// Model used for `review_file`; falls back to `model`
+    pub review_model: Option<String>,
+    /// Model used for `scaffold`; falls back to `model`
+    pub scaffold_model: Option<String>,
+    /// Model used for every task without a more specific setting
+    pub model: Option<String>,
+    pub max_tokens: u32,
+    /// Retries after a 429, 529 or 5xx response before giving up
+    pub max_retries: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    /// Deadline for one tool call's upstream request, retries included
+    pub request_timeout_secs: u64,
+}
+
+impl Default for LlmConfig {
+    fn default() -> Self {
+        Self {
+            backend: None,
+            api_key: None,
+            base_url: None,
+            review_model: None,
+            scaffold_model: None,
+            model: None,
+            max_tokens: 4096,
+            max_retries: 3,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 30_000,
+            request_timeout_secs: 120,
+        }
+    }
+}
+
+/// Which files the save watcher records history for.
+///
+/// `include` globs pick the tracked files: a pattern containing `/` matches
+/// the path relative to the project root, anything else the file name.
+/// `.gitignore` and `.ignore` files are always honored; `exclude` adds
+/// gitignore-style patterns on top. Setting either list replaces its default,
+/// so keep `*.rs` and `target/` in them.
+///
+/// ```toml
+/// [watcher]
+/// include = ["*.rs", "Cargo.toml", "Cargo.lock", "migrations/*.sql"]
+/// exclude = ["target/", "vendor/", "src/generated/"]
+/// session_gap_mins = 30
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct WatcherConfig {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    /// Minutes without a save after which the next save starts a new session
+    pub session_gap_mins: i64,
+}
+
+impl Default for WatcherConfig {
+    fn default() -> Self {
+        Self {
+            // build.rs is covered by *.rs and Cargo.toml by *.toml, but they
+            // are listed so trimming the list doesn't drop them by accident
+            include: [
+                "*.rs",
+                "build.rs",
+                "Cargo.toml",
+                "Cargo.lock",
+                "*.toml",
+                "*.sql",
+            ]
+            .map(String::from)
+            .to_vec(),
+            exclude: vec!["target/".to_string()],
+            session_gap_mins: 30,
+        }
+    }
+}
+
+/// Background `cargo check` after saves, so compiler diagnostics are kept
+/// alongside the change that caused them. Off by default since it competes
+/// with the student's own builds for the target directory lock.
+///
+/// ```toml
+/// [cargo_check]
+/// enabled = true
+/// clippy = true
+/// args = ["--all-targets"]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CargoCheckConfig {
+    pub enabled: bool,
+    /// Run `cargo clippy` instead of `cargo check`, so lints are tracked per save too
+    pub clippy: bool,
+    /// Extra arguments passed to `cargo check`
+    pub args: Vec<String>,
+}
+
+impl Config {
+    // load - this reads the config file (if any) and applies env overrides
+    pub fn load() -> Result<Self> {
+        let mut config = match Self::path() {
+            Some(path) if path.exists() => {
+                let text = std::fs::read_to_string(&path)
+                    .with_context(|| format!("failed to read {}", path.display()))?;
+                tracing::debug!(path = %path.display(), "loaded config file");
+                toml::from_str(&text)
+                    .with_context(|| format!("failed to parse {}", path.display()))?
+            }
+            _ => Self::default(),
+        };
+
+        config.llm.apply_env()?;
+        if config.llm.max_tokens == 0 {
+            bail!("max_tokens must be a positive integer");
+        }
+        Ok(config)
+    }
+
+    fn path() -> Option<PathBuf> {
+        std::env::var_os("RUST_TUTOR_CONFIG")
+            .map(PathBuf::from)
+            .or_else(|| {
+                Some(
+                    dirs::config_dir()?
+                        .join("rust-tutor-mcp")
+                        .join("config.toml"),
+                )
+            })
+    }
+}
+
+impl LlmConfig {
+    fn apply_env(&mut self) -> Result<()> {
+        override_from_env(&mut self.backend, "RUST_TUTOR_BACKEND");
+        override_from_env(&mut self.base_url, "RUST_TUTOR_BASE_URL");
+        override_from_env(&mut self.model, "RUST_TUTOR_MODEL");
+        override_from_env(&mut self.review_model, "RUST_TUTOR_REVIEW_MODEL");
+        override_from_env(&mut self.scaffold_model, "RUST_TUTOR_SCAFFOLD_MODEL");
+
+        if let Ok(max_tokens) = std::env::var("RUST_TUTOR_MAX_TOKENS") {
+            self.max_tokens = max_tokens
+                .parse::<NonZeroU32>()
+                .context("RUST_TUTOR_MAX_TOKENS must be a positive integer")?
+                .get();
+        }
+
+        Ok(())
+    }
+
+    // api_key_or_env - the configured key wins over the backend's usual env var
+    pub fn api_key_or_env(&self, var: &str) -> Option<String> {
+        self.api_key.clone().or_else(|| std::env::var(var).ok())
+    }
+
+    // base_url_or_env - RUST_TUTOR_BASE_URL / config wins over the backend's usual env var
+    pub fn base_url_or_env(&self, var: &str) -> Option<String> {
+        self.base_url.clone().or_else(|| std::env::var(var).ok())
+    }
+
+    // models - this resolves per-task model names against a backend default
+    pub fn models(&self, default_model: &str) -> ModelSettings {
+        let fallback = self.model.as_deref().unwrap_or(default_model);
+        ModelSettings {
+            review_model: self.review_model.as_deref().unwrap_or(fallback).to_string(),
+            scaffold_model: self
+                .scaffold_model
+                .as_deref()
+                .unwrap_or(fallback)
+                .to_string(),
+            max_tokens: self.max_tokens,
+        }
+    }
+}
+
+/// USD per million tokens.
+///
+/// ```toml
+/// [pricing."my-proxy-model"]
+/// input_per_mtok = 0.5
+/// output_per_mtok = 1.5
+/// ```
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct ModelPrice {
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+}
+
+/// Fully resolved model choices handed to a backend client.
+#[derive(Debug, Clone)]
+pub struct ModelSettings {
+    pub review_model: String,
+    pub scaffold_model: String,
+    pub max_tokens: u32,
+}
+
+impl ModelSettings {
+    pub fn model_for(&self, task: Task) -> &str {
+        match task {
+            Task::Review | Task::ReviewChanges => &self.review_model,
+            Task::Scaffold => &self.scaffold_model,
+        }
+    }
+}
+
+fn override_from_env(field: &mut Option<String>, var: &str) {
+    if let Ok(value) = std::env::var(var) {
+        if !value.is_empty() {
+            *field = Some(value);
+        }
+    }
+}
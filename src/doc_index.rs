@@ -0,0 +1,267 @@
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use crate::{
+    docs_rs::{self, Detail, DocItem},
+    manifest::LockedVersions,
+    store::DocIndexRecord,
+};
+
+// index_doc_dir - this indexes every crate rustdoc has written to `doc_dir`
+// (usually target/doc): html output from `cargo doc`, plus rustdoc JSON files
+// for crates without html. `only` limits it to one crate
+pub fn index_doc_dir(
+    doc_dir: &Path,
+    versions: &LockedVersions,
+    only: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<Vec<(String, Vec<DocIndexRecord>)>> {
+    let only = only.map(|name| name.replace('-', "_"));
+    let mut crates = Vec::new();
+    let mut json_files = Vec::new();
+
+    let mut entries: Vec<_> = fs::read_dir(doc_dir)
+        .with_context(|| format!("failed to read {}", doc_dir.display()))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect();
+    entries.sort();
+
+    for path in entries {
+        let Some(name) = path.file_stem().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if only.as_deref().is_some_and(|only| only != name) {
+            continue;
+        }
+        // src/, static.files/ and friends have no all.html
+        let all = path.join("all.html");
+        if all.is_file() {
+            let html = fs::read_to_string(&all)
+                .with_context(|| format!("failed to read {}", all.display()))?;
+            let records = docs_rs::parse_all_items(&html, &path.to_string_lossy())
+                .into_iter()
+                .map(|item| DocIndexRecord {
+                    crate_name: name.to_string(),
+                    version: versions.version(name).map(String::from),
+                    path: item.path,
+                    kind: item.kind,
+                    location: item.url,
+                    source: "html".to_string(),
+                    docs: None,
+                    indexed_at: now,
+                })
+                .collect();
+            crates.push((name.to_string(), records));
+        } else if path.extension().is_some_and(|ext| ext == "json") {
+            json_files.push(path);
+        }
+    }
+
+    for path in json_files {
+        let (name, records) = index_rustdoc_json(&path, versions, now)?;
+        if !crates.iter().any(|(indexed, _)| *indexed == name) {
+            crates.push((name, records));
+        }
+    }
+
+    Ok(crates)
+}
+
+// index_rustdoc_json - this reads the public items of one crate from a
+// `--output-format json` file. Only the crate's own items are kept, with their
+// docs, inherent methods and trait impls rendered up front since the json
+// has no page to go back to
+pub fn index_rustdoc_json(
+    path: &Path,
+    versions: &LockedVersions,
+    now: DateTime<Utc>,
+) -> Result<(String, Vec<DocIndexRecord>)> {
+    let text =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let doc: Value = serde_json::from_str(&text)
+        .with_context(|| format!("failed to parse {}", path.display()))?;
+
+    let index = &doc["index"];
+    let root = id_key(&doc["root"]);
+    let name = index[&root]["name"]
+        .as_str()
+        .with_context(|| format!("{} isn't rustdoc JSON", path.display()))?
+        .to_string();
+    let version = doc["crate_version"]
+        .as_str()
+        .map(String::from)
+        .or_else(|| versions.version(&name).map(String::from));
+
+    let mut records: Vec<DocIndexRecord> = doc["paths"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(_, summary)| summary["crate_id"] == 0)
+        .filter_map(|(id, summary)| {
+            let segments: Vec<&str> = summary["path"]
+                .as_array()?
+                .iter()
+                .filter_map(Value::as_str)
+                .collect();
+            let kind = html_kind(summary["kind"].as_str()?)?;
+            // the crate's own root is path [name]; modules aren't worth a hit
+            if segments.len() < 2 {
+                return None;
+            }
+            Some(DocIndexRecord {
+                crate_name: name.clone(),
+                version: version.clone(),
+                path: segments[1..].join("::"),
+                kind: kind.to_string(),
+                location: path.to_string_lossy().into_owned(),
+                source: "json".to_string(),
+                docs: index.get(id).map(|item| render_json_item(index, item)),
+                indexed_at: now,
+            })
+        })
+        .collect();
+    records.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok((name, records))
+}
+
+// html_kind - rustdoc JSON names kinds differently from the html file
+// prefixes `check_crate_docs` already speaks; modules and impls are skipped
+fn html_kind(kind: &str) -> Option<&str> {
+    Some(match kind {
+        "module" | "impl" | "use" | "extern_crate" | "struct_field" | "variant" => return None,
+        "function" => "fn",
+        "type_alias" => "type",
+        "trait_alias" => "traitalias",
+        "proc_attribute" => "attr",
+        "proc_derive" => "derive",
+        other => other,
+    })
+}
+
+// ids are numbers in newer format versions and strings in older ones
+fn id_key(id: &Value) -> String {
+    match id {
+        Value::String(id) => id.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn render_json_item(index: &Value, item: &Value) -> String {
+    let mut out = tidy_markdown(item["docs"].as_str().unwrap_or_default().trim());
+    let inner = item["inner"]
+        .as_object()
+        .and_then(|inner| inner.values().next());
+    let Some(inner) = inner else {
+        return out;
+    };
+
+    let mut methods = Vec::new();
+    let mut traits = Vec::new();
+    // a trait lists its own methods; a type reaches them through its impls
+    let mut collect_methods = |items: &Value| {
+        for id in items.as_array().into_iter().flatten() {
+            let method = &index[id_key(id)];
+            if method["inner"].get("function").is_none() {
+                continue;
+            }
+            let name = method["name"].as_str().unwrap_or_default();
+            match method["docs"].as_str().and_then(|d| d.lines().next()) {
+                Some(line) if !line.is_empty() => methods.push(format!("- `fn {name}`: {line}")),
+                _ => methods.push(format!("- `fn {name}`")),
+            }
+        }
+    };
+    collect_methods(&inner["items"]);
+    for id in inner["impls"].as_array().into_iter().flatten() {
+        let imp = &index[id_key(id)]["inner"]["impl"];
+        if imp["is_synthetic"] == true || !imp["blanket_impl"].is_null() {
+            continue;
+        }
+        match imp["trait"].as_object() {
+            None => collect_methods(&imp["items"]),
+            Some(tr) => {
+                let name = tr
+                    .get("path")
+                    .or_else(|| tr.get("name"))
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                traits.push(format!("- `{name}`"));
+            }
+        }
+    }
+
+    for (heading, lines) in [("Methods", methods), ("Trait implementations", traits)] {
+        if !lines.is_empty() {
+            out.push_str(&format!("\n\n### {heading}\n\n{}", lines.join("\n")));
+        }
+    }
+    out
+}
+
+// tidy_markdown - doc comments use `#` headings and bare ``` fences for
+// rust examples; this nests the headings under the item's and labels the fences
+fn tidy_markdown(docs: &str) -> String {
+    let mut in_code = false;
+    docs.lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if let Some(lang) = trimmed.strip_prefix("```") {
+                in_code = !in_code;
+                if in_code && lang.is_empty() {
+                    return "```rust".to_string();
+                }
+            } else if !in_code && trimmed.starts_with('#') {
+                return format!("#### {}", trimmed.trim_start_matches('#').trim());
+            }
+            line.to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// render - this renders an indexed item: html items by parsing their page
+// like a docs.rs one, json items from the markdown saved at index time
+pub fn render(record: &DocIndexRecord, detail: Detail) -> Result<String> {
+    if record.source == "html" {
+        let html = fs::read_to_string(&record.location)
+            .with_context(|| format!("failed to read {}", record.location))?;
+        let item = DocItem {
+            path: record.path.clone(),
+            kind: record.kind.clone(),
+            url: record.location.clone(),
+        };
+        return Ok(docs_rs::parse_item(&html, item).format_markdown(detail));
+    }
+
+    let docs = record.docs.as_deref().unwrap_or_default();
+    // the prose comes first, then the `###` sections added at index time
+    let (prose, sections) = match docs.find("\n\n### ") {
+        Some(at) => docs.split_at(at),
+        None => (docs, ""),
+    };
+    let prose = match detail {
+        Detail::Summary => prose.split("\n\n").next().unwrap_or_default(),
+        Detail::Full => prose,
+    };
+
+    let from = match &record.version {
+        Some(version) => format!("{} {version}", record.crate_name),
+        None => record.crate_name.clone(),
+    };
+    Ok(format!(
+        "## {} `{}`\n\n_{from}, from rustdoc JSON_\n\n{}{}\n",
+        record.kind,
+        record.path,
+        if prose.is_empty() {
+            "_No documentation_"
+        } else {
+            prose
+        },
+        sections
+    ))
+}
@@ -1,43 +1,692 @@
-use std::collections::HashMap;
+use std::sync::Mutex;
 
+use anyhow::bail;
 use anyhow::Context;
 use anyhow::Error;
 use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use scraper::ElementRef;
 use scraper::Html;
+use scraper::Node;
 use scraper::Selector;
 
+use crate::{
+    rustc,
+    store::{HttpCacheEntry, TutorStore},
+};
+
+// docs for an exact published version never change, so they're kept a long
+// time; `latest` and version requirements move with each release
+const PINNED_TTL_DAYS: i64 = 30;
+const FLOATING_TTL_HOURS: i64 = 6;
+
+// summary mode keeps long trait declarations and impl lists to a glance
+const SUMMARY_SIGNATURE_LINES: usize = 25;
+const SUMMARY_LIST_LIMIT: usize = 20;
+
+// item kinds as rustdoc names its pages, in the order equally good matches
+// are listed: types a student reaches for first, then functions and macros
+pub const KINDS: [&str; 14] = [
+    "struct",
+    "enum",
+    "trait",
+    "union",
+    "primitive",
+    "type",
+    "traitalias",
+    "fn",
+    "macro",
+    "derive",
+    "attr",
+    "constant",
+    "static",
+    "keyword",
+];
+
+// crates documented with the toolchain rather than on docs.rs
+const TOOLCHAIN_CRATES: [&str; 5] = ["std", "core", "alloc", "proc_macro", "test"];
+
+/// How a page was served, so callers can tell the user when docs may be out of date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freshness {
+    /// Downloaded just now
+    Fetched,
+    /// Served from the store within its TTL, or after docs.rs confirmed it unchanged
+    Cached(DateTime<Utc>),
+    /// docs.rs couldn't be reached; this is the last copy we have
+    Stale(DateTime<Utc>),
+    /// Read from the docs `rustup` installed with the toolchain
+    Local,
+}
+
+/// One entry from a crate's `all.html` item list.
+#[derive(Debug, Clone)]
+pub struct DocItem {
+    pub path: String, // de::Deserialize
+    pub kind: String, // struct, trait, fn, derive, ...
+    pub url: String,
+}
+
+/// The items of a crate matching a lookup, best match first.
+pub struct DocsLookup {
+    pub items: Vec<DocItem>,
+    pub freshness: Freshness,
+}
+
+/// How much of an item's page `check_crate_docs` renders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Detail {
+    /// Signature, first doc paragraph, method signatures and trait names
+    Summary,
+    /// Every doc section, each method's docs and all impls, auto and blanket included
+    Full,
+}
+
+/// The parts of a rustdoc item page worth showing a student.
+#[derive(Debug, Clone)]
+pub struct ItemDoc {
+    pub item: DocItem,
+    pub signature: Option<String>,
+    pub docs: Vec<String>, // markdown blocks, the summary paragraph first
+    pub methods: Vec<MethodDoc>,
+    pub trait_impls: Vec<String>,
+    pub auto_impls: Vec<String>, // auto traits and blanket impls
+    pub implementors: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MethodDoc {
+    pub signature: String,
+    pub docs: Vec<String>,
+}
+
+impl ItemDoc {
+    pub fn format_markdown(&self, detail: Detail) -> String {
+        let mut out = format!(
+            "## {} `{}`\n\n{}\n",
+            self.item.kind, self.item.path, self.item.url
+        );
+
+        if let Some(signature) = &self.signature {
+            let lines: Vec<&str> = signature.lines().collect();
+            let shown = match detail {
+                Detail::Summary if lines.len() > SUMMARY_SIGNATURE_LINES => {
+                    format!("{}\n    // …", lines[..SUMMARY_SIGNATURE_LINES].join("\n"))
+                }
+                _ => signature.clone(),
+            };
+            out.push_str(&format!("\n```rust\n{shown}\n```\n"));
+        }
+
+        match (detail, self.docs.first()) {
+            (_, None) => out.push_str("\n_No documentation_\n"),
+            (Detail::Summary, Some(summary)) => out.push_str(&format!("\n{summary}\n")),
+            (Detail::Full, Some(_)) => out.push_str(&format!("\n{}\n", self.docs.join("\n\n"))),
+        }
+
+        if !self.methods.is_empty() {
+            out.push_str("\n### Methods\n");
+            match detail {
+                Detail::Summary => {
+                    out.push('\n');
+                    for method in self.methods.iter().take(SUMMARY_LIST_LIMIT) {
+                        match method.docs.first().and_then(|d| d.lines().next()) {
+                            Some(line) => {
+                                out.push_str(&format!("- `{}`: {}\n", method.signature, line))
+                            }
+                            None => out.push_str(&format!("- `{}`\n", method.signature)),
+                        }
+                    }
+                    push_overflow(&mut out, self.methods.len());
+                }
+                Detail::Full => {
+                    for method in &self.methods {
+                        out.push_str(&format!("\n#### `{}`\n", method.signature));
+                        if !method.docs.is_empty() {
+                            out.push_str(&format!("\n{}\n", method.docs.join("\n\n")));
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut groups = vec![
+            ("Trait implementations", &self.trait_impls),
+            ("Implementors", &self.implementors),
+        ];
+        if detail == Detail::Full {
+            groups.push(("Auto and blanket implementations", &self.auto_impls));
+        }
+        for (heading, impls) in groups.into_iter().filter(|(_, i)| !i.is_empty()) {
+            out.push_str(&format!("\n### {heading}\n\n"));
+            let shown = match detail {
+                Detail::Summary => SUMMARY_LIST_LIMIT,
+                Detail::Full => impls.len(),
+            };
+            for line in impls.iter().take(shown) {
+                out.push_str(&format!("- `{line}`\n"));
+            }
+            if detail == Detail::Summary {
+                push_overflow(&mut out, impls.len());
+            }
+        }
+
+        out
+    }
+}
+
+fn push_overflow(out: &mut String, total: usize) {
+    if total > SUMMARY_LIST_LIMIT {
+        out.push_str(&format!(
+            "- … and {} more (`detail = \"full\"` lists them all)\n",
+            total - SUMMARY_LIST_LIMIT
+        ));
+    }
+}
+
 // fetch_docs - used to get type information for dependencies
 pub async fn fetch_docs(
     client: &reqwest::Client,
+    store: &Mutex<TutorStore>,
     crate_name: &str,
     type_name: &str,
+    kind: Option<&str>,
     version: &str,
-) -> Result<HashMap<String, String>, Error> {
-    let web_name = crate_name.replace('-', "_");
+) -> Result<DocsLookup, Error> {
+    let (base, docs_html, freshness) = if is_toolchain_crate(crate_name) {
+        fetch_toolchain_items(client, store, crate_name, version).await?
+    } else {
+        let web_name = crate_name.replace('-', "_");
 
-    let base = format!("https://docs.rs/{}/{}/{}", crate_name, version, web_name);
+        let base = format!("https://docs.rs/{}/{}/{}", crate_name, version, web_name);
 
-    let url = format!("{}/all.html", base);
+        let url = format!("{}/all.html", base);
 
-    let docs_html = client
-        .get(&url)
-        .send()
-        .await
-        .with_context(|| format!("Failed to get docs for {}", crate_name))?
-        .text()
-        .await
-        .with_context(|| format!("Failed to get html for {}", crate_name))?;
+        let (docs_html, freshness) = fetch_cached(client, store, &url, crate_name, version).await?;
+        (base, docs_html, freshness)
+    };
+
+    let mut ranked: Vec<(MatchRank, DocItem)> = parse_all_items(&docs_html, &base)
+        .into_iter()
+        .filter(|item| kind.is_none_or(|kind| item.kind == kind))
+        .filter_map(|item| Some((rank(&item.path, &item.kind, type_name)?, item)))
+        .collect();
+    ranked.sort_by(|a, b| a.0.cmp(&b.0));
+    let items = ranked.into_iter().map(|(_, item)| item).collect();
 
-    Ok(Html::parse_document(&docs_html)
+    Ok(DocsLookup { items, freshness })
+}
+
+pub fn is_toolchain_crate(crate_name: &str) -> bool {
+    TOOLCHAIN_CRATES.contains(&crate_name)
+}
+
+// fetch_toolchain_items - this gets `all.html` for std, core and friends:
+// from `rustup doc`'s local copy when it matches the requested version, else
+// from doc.rust-lang.org for the installed release (or `version`). Returns the
+// base items resolve against along with the page
+async fn fetch_toolchain_items(
+    client: &reqwest::Client,
+    store: &Mutex<TutorStore>,
+    crate_name: &str,
+    version: &str,
+) -> Result<(String, String, Freshness)> {
+    let (release, docs_dir) =
+        tokio::task::spawn_blocking(|| (rustc::release(), rustc::docs_dir())).await?;
+
+    if let (Some(release), Some(docs_dir)) = (&release, docs_dir) {
+        if version == "latest" || version == release {
+            let base = docs_dir.join(crate_name);
+            if let Ok(html) = tokio::fs::read_to_string(base.join("all.html")).await {
+                return Ok((base.to_string_lossy().into_owned(), html, Freshness::Local));
+            }
+        }
+    }
+
+    let channel = match (version, &release) {
+        ("latest", Some(release)) => rustc::doc_channel(release),
+        ("latest", None) => "stable",
+        (version, _) => rustc::doc_channel(version),
+    };
+    let base = format!("https://doc.rust-lang.org/{channel}/{crate_name}");
+    let url = format!("{base}/all.html");
+    let (html, freshness) = fetch_cached(client, store, &url, crate_name, channel).await?;
+    Ok((base, html, freshness))
+}
+
+// parse_all_items - this reads every item from a crate's `all.html`, with
+// urls resolved against `base`
+pub fn parse_all_items(html: &str, base: &str) -> Vec<DocItem> {
+    Html::parse_document(html)
         .select(&Selector::parse("ul.all-items a").unwrap())
         .filter_map(|item| {
-            let name = item.inner_html();
-            if name.to_lowercase().contains(&type_name.to_lowercase()) {
-                let href = format!("{}/{}", base, item.attr("href")?);
-                Some((name, href))
-            } else {
-                None
+            // newer rustdoc breaks long paths with <wbr>, so read the text, not the html
+            let path: String = item.text().collect();
+            let href = item.attr("href")?;
+            let kind = href.rsplit('/').next()?.split('.').next()?.to_string();
+            Some(DocItem {
+                path,
+                kind,
+                url: format!("{}/{}", base, href),
+            })
+        })
+        .collect()
+}
+
+/// Sort key for a search hit; lower is better, and no two items tie.
+pub type MatchRank = (u8, usize, usize, usize, usize, String, String);
+
+// rank - this scores `path` against a search: exact names beat prefixes
+// beat substrings beat near misses (typos within a couple of edits). Among
+// equals, shallower paths and types before functions and macros, then
+// shorter, then alphabetical. None when it doesn't match at all
+pub fn rank(path: &str, kind: &str, query: &str) -> Option<MatchRank> {
+    let query = query.trim();
+    // `de::Visitor` is matched against the whole path, `Visitor` against the name
+    let name = if query.contains("::") {
+        path
+    } else {
+        path.rsplit("::").next().unwrap_or(path)
+    };
+    let (name_lower, needle) = (name.to_lowercase(), query.to_lowercase());
+
+    let (closeness, distance) = if name == query {
+        (0, 0)
+    } else if name_lower == needle {
+        (1, 0)
+    } else if name_lower.starts_with(&needle) {
+        (2, 0)
+    } else if path.to_lowercase().contains(&needle) {
+        (3, 0)
+    } else {
+        let distance = edit_distance(&name_lower, &needle);
+        if distance > typo_allowance(&needle) {
+            return None;
+        }
+        (4, distance)
+    };
+
+    let kind_order = KINDS.iter().position(|k| *k == kind).unwrap_or(KINDS.len());
+    Some((
+        closeness,
+        distance,
+        path.matches("::").count(),
+        kind_order,
+        path.len(),
+        path.to_string(),
+        kind.to_string(),
+    ))
+}
+
+// short names get no slack: `Rc` one edit away is `Arc`, not a typo
+fn typo_allowance(needle: &str) -> usize {
+    match needle.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+// edit_distance - Levenshtein distance counting a swap of neighbours as one
+// edit, since `HsahMap` is one slip, not two
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
             }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+// normalize_kind - this maps the kind names people (and rustdoc JSON) use
+// onto rustdoc's page prefixes; None for anything that isn't a kind
+pub fn normalize_kind(kind: &str) -> Option<&'static str> {
+    let kind = kind.trim().to_lowercase();
+    let kind = match kind.as_str() {
+        "function" | "method" => "fn",
+        "type_alias" | "alias" => "type",
+        "trait_alias" => "traitalias",
+        "const" => "constant",
+        "proc_derive" | "derive_macro" => "derive",
+        "attribute" | "proc_attribute" => "attr",
+        other => other,
+    };
+    KINDS.iter().find(|k| **k == kind).copied()
+}
+
+// fetch_item - this downloads (or reads from the cache) one item's page and
+// pulls out the parts `check_crate_docs` renders
+pub async fn fetch_item(
+    client: &reqwest::Client,
+    store: &Mutex<TutorStore>,
+    crate_name: &str,
+    version: &str,
+    item: &DocItem,
+) -> Result<(ItemDoc, Freshness)> {
+    // toolchain docs from `rustup doc` are plain file paths
+    let (html, freshness) = if item.url.starts_with("https://") {
+        fetch_cached(client, store, &item.url, crate_name, version).await?
+    } else {
+        let html = tokio::fs::read_to_string(&item.url)
+            .await
+            .with_context(|| format!("failed to read {}", item.url))?;
+        (html, Freshness::Local)
+    };
+    Ok((parse_item(&html, item.clone()), freshness))
+}
+
+// parse_item - this reads the rustdoc layout docs.rs has served since 2021
+// (`.item-decl`, `details.top-doc`, `section.method`); older pages just come
+// back mostly empty
+pub fn parse_item(html: &str, item: DocItem) -> ItemDoc {
+    let page = Html::parse_document(html);
+    let select = |selector: &str| Selector::parse(selector).unwrap();
+
+    let signature = page
+        .select(&select(".item-decl"))
+        .next()
+        // where clauses sit on their own lines, as rustdoc lays them out
+        .map(|decl| block_text(decl, '\n').trim().replace(",\n\n", ",\n"));
+
+    let docs = page
+        .select(&select(
+            "details.top-doc > .docblock, #main-content > .docblock",
+        ))
+        .next()
+        .map(render_blocks)
+        .unwrap_or_default();
+
+    let methods = page
+        .select(&select(
+            "#implementations-list section.method, div.methods section.method",
+        ))
+        .filter_map(|section| {
+            let signature = code_text(section.select(&select(".code-header")).next()?);
+            // documented methods sit in <details><summary><section/></summary><div.docblock/>
+            let docs = section
+                .parent()
+                .and_then(|summary| summary.parent())
+                .and_then(ElementRef::wrap)
+                .filter(|details| details.value().name() == "details")
+                .and_then(|details| {
+                    details
+                        .children()
+                        .filter_map(ElementRef::wrap)
+                        .find(|el| el.value().classes().any(|c| c == "docblock"))
+                })
+                .map(render_blocks)
+                .unwrap_or_default();
+            Some(MethodDoc { signature, docs })
         })
-        .collect())
+        .collect();
+
+    let impls = |list: &str| -> Vec<String> {
+        page.select(&select(&format!("{list} section.impl > .code-header")))
+            .map(code_text)
+            .collect()
+    };
+
+    let mut auto_impls = impls("#synthetic-implementations-list");
+    auto_impls.extend(impls("#blanket-implementations-list"));
+
+    ItemDoc {
+        item,
+        signature,
+        docs,
+        methods,
+        trait_impls: impls("#trait-implementations-list"),
+        auto_impls,
+        implementors: impls("#implementors-list"),
+    }
+}
+
+// render_blocks - this turns a rustdoc docblock into markdown, one string per
+// paragraph, list, code example or heading
+fn render_blocks(docblock: ElementRef) -> Vec<String> {
+    let mut blocks = Vec::new();
+    for child in docblock.children() {
+        let Some(el) = ElementRef::wrap(child) else {
+            if let Node::Text(text) = child.value() {
+                let text = collapse_whitespace(text);
+                if !text.is_empty() {
+                    blocks.push(text);
+                }
+            }
+            continue;
+        };
+        let block = match el.value().name() {
+            "pre" => format!("```rust\n{}\n```", el.text().collect::<String>().trim_end()),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => format!("#### {}", render_inline(el)),
+            "ul" | "ol" => el
+                .children()
+                .filter_map(ElementRef::wrap)
+                .filter(|li| li.value().name() == "li")
+                .map(|li| format!("- {}", render_inline(li)))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            "div" | "blockquote" | "section" => {
+                blocks.extend(render_blocks(el));
+                continue;
+            }
+            _ => render_inline(el),
+        };
+        if !block.trim().is_empty() {
+            blocks.push(block);
+        }
+    }
+    blocks
+}
+
+fn render_inline(el: ElementRef) -> String {
+    let mut out = String::new();
+    for child in el.children() {
+        match ElementRef::wrap(child) {
+            None => {
+                if let Node::Text(text) = child.value() {
+                    out.push_str(text);
+                }
+            }
+            // the § anchors next to headings and the "Run" button on examples
+            Some(el)
+                if el
+                    .value()
+                    .classes()
+                    .any(|c| c == "doc-anchor" || c == "test-arrow") => {}
+            Some(el) => match el.value().name() {
+                "code" => out.push_str(&format!("`{}`", el.text().collect::<String>())),
+                "em" | "i" => out.push_str(&format!("*{}*", render_inline(el))),
+                "strong" | "b" => out.push_str(&format!("**{}**", render_inline(el))),
+                "br" => out.push(' '),
+                _ => out.push_str(&render_inline(el)),
+            },
+        }
+    }
+    collapse_whitespace(&out)
+}
+
+// code_text - this flattens a code header onto one line; the `where` clause
+// is a <div> butted up against the type, so block elements get a space
+fn code_text(header: ElementRef) -> String {
+    // long parameter lists are laid out one per line, with a trailing comma
+    collapse_whitespace(block_text(header, ' ').trim_end_matches([',', ' ', '\n']))
+        .replace("( ", "(")
+        .replace(", )", ")")
+}
+
+// block_text - this reads an element's text with `separator` around its
+// block elements, skipping the ⓘ marker that pops up a type's notable traits
+// and the "Show 76 methods" toggle on long traits
+fn block_text(el: ElementRef, separator: char) -> String {
+    fn walk(el: ElementRef, separator: char, out: &mut String) {
+        for child in el.children() {
+            match ElementRef::wrap(child) {
+                Some(el)
+                    if el.value().name() == "summary"
+                        || el.value().classes().any(|c| c == "tooltip") => {}
+                Some(el) if el.value().name() == "div" => {
+                    out.push(separator);
+                    walk(el, separator, out);
+                    out.push(separator);
+                }
+                Some(el) => walk(el, separator, out),
+                None => {
+                    if let Node::Text(text) = child.value() {
+                        out.push_str(text);
+                    }
+                }
+            }
+        }
+    }
+    let mut out = String::new();
+    walk(el, separator, &mut out);
+    out
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// fetch_cached - this serves `url` from the store while it's fresh, then
+// revalidates with its ETag / Last-Modified, falling back to the stale copy
+// when docs.rs can't be reached
+pub async fn fetch_cached(
+    client: &reqwest::Client,
+    store: &Mutex<TutorStore>,
+    url: &str,
+    crate_name: &str,
+    version: &str,
+) -> Result<(String, Freshness)> {
+    let cached = store
+        .lock()
+        .expect("store lock poisoned")
+        .get_http_cache(url)
+        .unwrap_or_else(|e| {
+            tracing::warn!("failed to read docs cache: {e}");
+            None
+        });
+
+    let now = Utc::now();
+    if let Some(entry) = &cached {
+        if entry.expires_at > now {
+            return Ok((entry.body.clone(), Freshness::Cached(entry.fetched_at)));
+        }
+    }
+
+    let mut request = client.get(url);
+    if let Some(entry) = &cached {
+        if let Some(etag) = &entry.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &entry.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(e) => {
+            return match cached {
+                Some(entry) => {
+                    tracing::warn!("docs.rs unreachable, serving cached {url}: {e}");
+                    Ok((entry.body, Freshness::Stale(entry.fetched_at)))
+                }
+                None => Err(e).with_context(|| format!("Failed to get docs for {}", crate_name)),
+            };
+        }
+    };
+
+    let expires_at = now + ttl(version);
+    let status = response.status();
+
+    if status == StatusCode::NOT_MODIFIED {
+        if let Some(entry) = cached {
+            if let Err(e) = store
+                .lock()
+                .expect("store lock poisoned")
+                .refresh_http_cache(url, now, expires_at)
+            {
+                tracing::warn!("failed to refresh docs cache: {e}");
+            }
+            return Ok((entry.body, Freshness::Cached(entry.fetched_at)));
+        }
+    }
+
+    if !status.is_success() {
+        if status.is_server_error() {
+            if let Some(entry) = cached {
+                tracing::warn!("docs.rs returned {status}, serving cached {url}");
+                return Ok((entry.body, Freshness::Stale(entry.fetched_at)));
+            }
+        }
+        bail!("docs.rs returned {status} for {crate_name} {version}");
+    }
+
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from)
+    };
+    let etag = header(ETAG);
+    let last_modified = header(LAST_MODIFIED);
+
+    let body = response
+        .text()
+        .await
+        .with_context(|| format!("Failed to get html for {}", crate_name))?;
+
+    let entry = HttpCacheEntry {
+        url: url.to_string(),
+        crate_name: crate_name.to_string(),
+        version: version.to_string(),
+        body,
+        etag,
+        last_modified,
+        fetched_at: now,
+        expires_at,
+    };
+    if let Err(e) = store
+        .lock()
+        .expect("store lock poisoned")
+        .save_http_cache(&entry)
+    {
+        tracing::warn!("failed to cache docs: {e}");
+    }
+
+    Ok((entry.body, Freshness::Fetched))
+}
+
+// ttl - an exact `x.y.z` version is immutable on docs.rs; anything else can
+// resolve to a newer release tomorrow
+fn ttl(version: &str) -> Duration {
+    // strip pre-release and build metadata: `1.0.0-beta.1+abc` is as fixed as `1.0.0`
+    let core = version.split(['-', '+']).next().unwrap_or_default();
+    let parts: Vec<&str> = core.split('.').collect();
+    let pinned = parts.len() == 3
+        && parts
+            .iter()
+            .all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()));
+    if pinned {
+        Duration::days(PINNED_TTL_DAYS)
+    } else {
+        Duration::hours(FLOATING_TTL_HOURS)
+    }
 }
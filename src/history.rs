@@ -0,0 +1,108 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+use crate::store::{FileChangeRecord, TutorStore};
+
+/// A file's contents at some point in its recorded history.
+#[derive(Debug, Clone)]
+pub enum FileVersion {
+    Present(String),
+    /// Deleted, or renamed to another path, at the given time
+    Gone {
+        at: DateTime<Utc>,
+        renamed_to: Option<String>,
+    },
+    /// Nothing was recorded for the path by then
+    Unknown,
+}
+
+pub fn content_hash(contents: &str) -> String {
+    format!("{:x}", Sha256::digest(contents.as_bytes()))
+}
+
+// file_at - this rebuilds a file as of `at` from its newest snapshot at or
+// before then, replaying every later save's hunks in order
+pub fn file_at(store: &TutorStore, file_path: &str, at: DateTime<Utc>) -> Result<FileVersion> {
+    let snapshot = store.latest_snapshot(file_path, at)?;
+    let since = snapshot.as_ref().map(|s| s.created_at);
+    let mut contents = snapshot.map(|s| s.content);
+
+    let history = store.get_history_after(file_path, since, at)?;
+    let mut gone = None;
+
+    for save in group_by_change(&history) {
+        let first = save[0];
+
+        if first.file_path != file_path {
+            // renamed away from this path
+            gone = Some((first.changed_at, Some(first.file_path.clone())));
+            contents = None;
+            continue;
+        }
+
+        match first.kind.as_str() {
+            "delete" => {
+                gone = Some((first.changed_at, None));
+                contents = None;
+            }
+            _ => {
+                let Some(base) = contents.as_deref() else {
+                    bail!(
+                        "no snapshot of `{file_path}` before the save at {}, so it can't be rebuilt",
+                        first.changed_at
+                    );
+                };
+                contents = Some(apply_save(base, &save)?);
+                gone = None;
+            }
+        }
+    }
+
+    Ok(match (contents, gone) {
+        (Some(contents), _) => FileVersion::Present(contents),
+        (None, Some((at, renamed_to))) => FileVersion::Gone { at, renamed_to },
+        (None, None) => FileVersion::Unknown,
+    })
+}
+
+// group_by_change - this splits time-ordered hunks into one slice per save
+fn group_by_change(history: &[FileChangeRecord]) -> Vec<Vec<&FileChangeRecord>> {
+    let mut saves: Vec<Vec<&FileChangeRecord>> = Vec::new();
+    for hunk in history {
+        match saves.last_mut() {
+            Some(save) if save[0].change_id == hunk.change_id => save.push(hunk),
+            _ => saves.push(vec![hunk]),
+        }
+    }
+    saves
+}
+
+// apply_save - this replaces each hunk's old line range with its exact new
+// text, back to front so earlier line numbers stay valid
+fn apply_save(base: &str, save: &[&FileChangeRecord]) -> Result<String> {
+    let mut lines: Vec<&str> = base.split_inclusive('\n').collect();
+    let mut hunks = save.to_vec();
+    hunks.sort_by_key(|h| std::cmp::Reverse(h.hunk_idx));
+
+    for hunk in hunks {
+        let Some(new_text) = hunk.new_text.as_deref() else {
+            bail!(
+                "the save at {} was recorded before exact history was kept, so it can't be replayed",
+                hunk.changed_at
+            );
+        };
+        let start = hunk.old_start as usize;
+        let end = start + hunk.old_count as usize;
+        if end > lines.len() {
+            bail!(
+                "hunk {} of the save at {} doesn't fit the file it applies to",
+                hunk.hunk_idx,
+                hunk.changed_at
+            );
+        }
+        lines.splice(start..end, new_text.split_inclusive('\n'));
+    }
+
+    Ok(lines.concat())
+}
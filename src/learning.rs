@@ -0,0 +1,213 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::store::{CodeSummary, WeeklyChecks, WeeklyCode};
+
+// a code counts as learned once this many on-save checks have passed without it...
+const LEARNED_AFTER_CHECKS: i64 = 10;
+// ...and it has been quiet for this long
+const QUIET_DAYS: i64 = 7;
+// hits (or weeks) after which a code is a pattern rather than a one-off slip
+const RECURRING_HITS: i64 = 3;
+// rows per report section
+const SECTION_LIMIT: usize = 10;
+
+// rustc error codes grouped by the concept a student is missing when they hit them
+const TOPICS: &[(&[&str], &str)] = &[
+    (
+        &["E0382", "E0505", "E0507", "E0509"],
+        "ownership and moves (The Book, ch. 4.1)",
+    ),
+    (
+        &["E0499", "E0502", "E0506", "E0594", "E0596"],
+        "borrowing and references (The Book, ch. 4.2)",
+    ),
+    (
+        &["E0106", "E0495", "E0515", "E0597", "E0621", "E0716"],
+        "lifetimes (The Book, ch. 10.3)",
+    ),
+    (
+        &["E0038", "E0207", "E0277"],
+        "traits and trait bounds (The Book, ch. 10.2)",
+    ),
+    (
+        &["E0308", "E0599", "E0614"],
+        "types and method resolution (The Book, ch. 3 and 5.3)",
+    ),
+    (
+        &["E0425", "E0432", "E0433", "E0603"],
+        "modules, paths and visibility (The Book, ch. 7)",
+    ),
+    (
+        &["E0004", "E0005"],
+        "pattern matching (The Book, ch. 6 and 19)",
+    ),
+];
+
+/// Where a student stands with one error code or lint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Progress {
+    /// Keeps coming back across saves or weeks
+    Recurring,
+    /// Showed up recently, but only once or twice so far
+    New,
+    /// Quiet lately, but too few checks since to call it learned
+    Fading,
+    /// Stopped showing up despite plenty of checks since
+    Learned,
+}
+
+// classify - this decides a code's progress from its history
+pub fn classify(summary: &CodeSummary, now: DateTime<Utc>) -> Progress {
+    let quiet = now - summary.last_seen >= Duration::days(QUIET_DAYS);
+    match (quiet, summary.checks_since >= LEARNED_AFTER_CHECKS) {
+        (true, true) => Progress::Learned,
+        (true, false) => Progress::Fading,
+        _ if summary.hits >= RECURRING_HITS || summary.weeks >= 2 => Progress::Recurring,
+        _ => Progress::New,
+    }
+}
+
+pub fn topic_for(code: &str) -> Option<&'static str> {
+    TOPICS
+        .iter()
+        .find(|(codes, _)| codes.contains(&code))
+        .map(|(_, topic)| *topic)
+}
+
+// build_report - this renders the markdown learning report from the store's
+// per-code summaries and the weekly breakdown for the last `weeks` weeks
+pub fn build_report(
+    summaries: &[CodeSummary],
+    weekly_checks: &[WeeklyChecks],
+    weekly_codes: &[WeeklyCode],
+    weeks: i64,
+    now: DateTime<Utc>,
+) -> String {
+    if summaries.is_empty() && weekly_checks.is_empty() {
+        return "No compiler errors or clippy lints recorded yet. Set `enabled = true` under `[cargo_check]` in config.toml (and `clippy = true` to track lints) so saves get checked".to_string();
+    }
+
+    let checks: i64 = weekly_checks.iter().map(|w| w.checks).sum();
+    let failing: i64 = weekly_checks.iter().map(|w| w.failing).sum();
+    let mut out = format!(
+        "# Learning report\n\n_Last {weeks} weeks: {checks} checked saves, {failing} didn't compile_\n"
+    );
+
+    if !weekly_checks.is_empty() {
+        out.push_str(
+            "\n## By week\n\n| Week of | Checks | Failing | Most hit |\n|---|---:|---:|---|\n",
+        );
+        for week in weekly_checks {
+            let top = weekly_codes
+                .iter()
+                .filter(|c| c.week == week.week)
+                .take(3)
+                .map(|c| format!("`{}` ×{}", c.code, c.hits))
+                .collect::<Vec<_>>();
+            out.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                week.week,
+                week.checks,
+                week.failing,
+                if top.is_empty() {
+                    "-".to_string()
+                } else {
+                    top.join(", ")
+                }
+            ));
+        }
+    }
+
+    let by_progress = |progress: Progress| {
+        summaries
+            .iter()
+            .filter(move |s| classify(s, now) == progress)
+    };
+
+    let recurring: Vec<&CodeSummary> = by_progress(Progress::Recurring).collect();
+    if !recurring.is_empty() {
+        out.push_str("\n## Still struggling\n\n| Code | Hits | Weeks | Last seen | Topic |\n|---|---:|---:|---|---|\n");
+        for s in recurring.iter().take(SECTION_LIMIT) {
+            out.push_str(&format!(
+                "| `{}` | {} | {} | {} | {} |\n",
+                s.code,
+                s.hits,
+                s.weeks,
+                s.last_seen.format("%Y-%m-%d"),
+                describe_topic(&s.code)
+            ));
+        }
+    }
+
+    let sections = [
+        (Progress::New, "New lately"),
+        (
+            Progress::Fading,
+            "Fading (quiet, but not enough checks since to be sure)",
+        ),
+        (Progress::Learned, "Learned"),
+    ];
+    for (progress, heading) in sections {
+        let items: Vec<String> = by_progress(progress)
+            .take(SECTION_LIMIT)
+            .map(|s| match progress {
+                Progress::Learned => format!(
+                    "- `{}`: {} hit{} from {} to {}, {} checks since",
+                    s.code,
+                    s.hits,
+                    if s.hits == 1 { "" } else { "s" },
+                    s.first_seen.format("%Y-%m-%d"),
+                    s.last_seen.format("%Y-%m-%d"),
+                    s.checks_since
+                ),
+                _ => format!(
+                    "- `{}` ({} hit{}, last {}): {}",
+                    s.code,
+                    s.hits,
+                    if s.hits == 1 { "" } else { "s" },
+                    s.last_seen.format("%Y-%m-%d"),
+                    s.message
+                ),
+            })
+            .collect();
+        if !items.is_empty() {
+            out.push_str(&format!("\n## {heading}\n\n{}\n", items.join("\n")));
+        }
+    }
+
+    out.push_str("\n## Suggested next topic\n\n");
+    // summaries come most-hit first, so the first recurring code is the biggest struggle
+    match recurring.first() {
+        Some(s) if s.code.starts_with('E') => out.push_str(&format!(
+            "Work on **{}**: `{}` came up in {} saves across {} week{}. `explain_error` with `{}` walks through why it happens.\n",
+            topic_for(&s.code).unwrap_or("the concept behind this error"),
+            s.code,
+            s.hits,
+            s.weeks,
+            if s.weeks == 1 { "" } else { "s" },
+            s.code
+        )),
+        Some(s) => out.push_str(&format!(
+            "Look at `{}`, which clippy flagged in {} saves: {}. {}\n",
+            s.code,
+            s.hits,
+            s.message,
+            describe_topic(&s.code)
+        )),
+        None => out.push_str(
+            "Nothing keeps recurring yet. Keep going with the current material\n",
+        ),
+    }
+
+    out
+}
+
+fn describe_topic(code: &str) -> String {
+    match (topic_for(code), code.strip_prefix("clippy::")) {
+        (Some(topic), _) => topic.to_string(),
+        (None, Some(lint)) => format!(
+            "idiomatic Rust, see https://rust-lang.github.io/rust-clippy/master/index.html#{lint}"
+        ),
+        (None, None) => "-".to_string(),
+    }
+}
@@ -1,16 +1,31 @@
+mod backend;
+mod cargo;
+mod changes;
 mod claude;
+mod config;
+mod doc_index;
 mod docs_rs;
+mod history;
+mod learning;
 mod man;
+mod manifest;
+mod migrations;
+mod ollama;
+mod openai;
+mod prompts;
+mod retry;
+mod rustc;
+mod sampling;
 mod server;
 mod store;
 mod tools;
+mod usage;
 mod watcher;
 
 use rmcp::{transport::stdio, ServiceExt};
 use tracing_subscriber::EnvFilter;
 
-use crate::claude::ClaudeClient;
-use crate::server::RustTutor;
+use crate::{config::Config, server::RustTutor};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -21,18 +36,14 @@ async fn main() -> anyhow::Result<()> {
         .with_writer(std::io::stderr)
         .init();
 
-    let claude = match std::env::var("ANTHROPIC_API_KEY") {
-        Ok(key) => {
-            tracing::info!("ANTHROPIC_API_KEY set — reviews will use Claude API");
-            Some(ClaudeClient::new(key))
-        }
-        Err(_) => {
-            tracing::info!("No ANTHROPIC_API_KEY — reviews will be delegated to host LLM");
-            None
-        }
-    };
-
-    let tutor = RustTutor::new(claude)?;
+    let config = Config::load()?;
+    let backend = backend::from_config(&config.llm)?;
+    match &backend {
+        Some(b) => tracing::info!("reviews will use the {} backend", b.name()),
+        None => tracing::info!("No LLM backend configured — reviews will be delegated to host LLM"),
+    }
+
+    let tutor = RustTutor::new(&config, backend)?;
 
     tracing::info!("Starting Rust Tutor MCP server");
 
@@ -0,0 +1,320 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use toml::{Table, Value};
+
+/// One dependency as declared in a manifest, or one resolved package in a lockfile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dependency {
+    /// `dependencies`, `dev-dependencies`, `target.'cfg(unix)'.dependencies`, `Cargo.lock`, ...
+    pub section: String,
+    pub version: Option<String>,
+    /// Features the declaration asks for
+    pub features: Vec<String>,
+    /// False for `default-features = false`
+    pub default_features: bool,
+    // the whole declaration, so feature or source edits still count as a change
+    spec: String,
+}
+
+/// How a dependency differs between two versions of a manifest or lockfile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DependencyChange {
+    pub crate_name: String,
+    pub section: String,
+    pub kind: DependencyChangeKind,
+    pub old_version: Option<String>,
+    pub new_version: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DependencyChangeKind {
+    Added,
+    Removed,
+    /// The version requirement moved
+    Bumped,
+    /// Same version, but features, source or other keys changed
+    Changed,
+}
+
+impl DependencyChangeKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DependencyChangeKind::Added => "added",
+            DependencyChangeKind::Removed => "removed",
+            DependencyChangeKind::Bumped => "bumped",
+            DependencyChangeKind::Changed => "changed",
+        }
+    }
+}
+
+// keyed by (section, crate name)
+pub type Dependencies = BTreeMap<(String, String), Dependency>;
+
+const DEPENDENCY_TABLES: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+// parse_manifest - this collects every dependency a Cargo.toml declares,
+// including target-specific and workspace dependencies
+pub fn parse_manifest(text: &str) -> Result<Dependencies> {
+    let manifest: Table = toml::from_str(text).context("failed to parse Cargo.toml")?;
+    let mut deps = Dependencies::new();
+
+    collect_tables(&manifest, "", &mut deps);
+
+    if let Some(targets) = manifest.get("target").and_then(Value::as_table) {
+        for (target, table) in targets {
+            if let Some(table) = table.as_table() {
+                collect_tables(table, &format!("target.'{target}'."), &mut deps);
+            }
+        }
+    }
+
+    if let Some(workspace) = manifest.get("workspace").and_then(Value::as_table) {
+        collect_tables(workspace, "workspace.", &mut deps);
+    }
+
+    Ok(deps)
+}
+
+fn collect_tables(table: &Table, prefix: &str, deps: &mut Dependencies) {
+    for name in DEPENDENCY_TABLES {
+        let Some(entries) = table.get(name).and_then(Value::as_table) else {
+            continue;
+        };
+        let section = format!("{prefix}{name}");
+
+        for (crate_name, spec) in entries {
+            let version = match spec {
+                Value::String(version) => Some(version.clone()),
+                Value::Table(t) => t.get("version").and_then(Value::as_str).map(String::from),
+                _ => None,
+            };
+            let features = spec
+                .get("features")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(String::from)
+                .collect();
+            // `default_features` is the older spelling cargo still accepts
+            let default_features = spec
+                .get("default-features")
+                .or_else(|| spec.get("default_features"))
+                .and_then(Value::as_bool)
+                .unwrap_or(true);
+            // `foo = { package = "bar" }` renames; report the crate actually pulled in
+            let crate_name = spec
+                .get("package")
+                .and_then(Value::as_str)
+                .unwrap_or(crate_name);
+
+            deps.insert(
+                (section.clone(), crate_name.to_string()),
+                Dependency {
+                    section: section.clone(),
+                    version,
+                    features,
+                    default_features,
+                    spec: spec.to_string(),
+                },
+            );
+        }
+    }
+}
+
+// parse_lockfile - this lists the resolved packages in a Cargo.lock. A crate
+// locked at several versions is kept as one entry with the versions joined
+pub fn parse_lockfile(text: &str) -> Result<Dependencies> {
+    let lock: Table = toml::from_str(text).context("failed to parse Cargo.lock")?;
+    let mut versions: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+    for package in lock
+        .get("package")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let (Some(name), Some(version)) = (
+            package.get("name").and_then(Value::as_str),
+            package.get("version").and_then(Value::as_str),
+        ) else {
+            continue;
+        };
+        versions
+            .entry(name.to_string())
+            .or_default()
+            .push(version.to_string());
+    }
+
+    Ok(versions
+        .into_iter()
+        .map(|(name, mut versions)| {
+            versions.sort();
+            let version = versions.join(", ");
+            let dep = Dependency {
+                section: "Cargo.lock".to_string(),
+                version: Some(version.clone()),
+                features: Vec::new(),
+                default_features: true,
+                spec: version,
+            };
+            (("Cargo.lock".to_string(), name), dep)
+        })
+        .collect())
+}
+
+/// The versions a Cargo.lock pins, with the exact version the workspace's
+/// own crates use for each of their direct dependencies.
+#[derive(Debug, Clone, Default)]
+pub struct LockedVersions {
+    // every locked version of each package, by name
+    packages: BTreeMap<String, Vec<String>>,
+    // direct dependencies of workspace members (packages without a source)
+    direct: BTreeMap<String, String>,
+}
+
+impl LockedVersions {
+    // version - this picks the version docs should be shown for: the one the
+    // workspace depends on directly, else the newest locked. Dashes and
+    // underscores are interchangeable, as with rustdoc's crate names
+    pub fn version(&self, crate_name: &str) -> Option<&str> {
+        let name = crate_name.replace('_', "-");
+        let lookup = |map_key: &String| map_key.replace('_', "-") == name;
+        if let Some((_, version)) = self.direct.iter().find(|(key, _)| lookup(key)) {
+            return Some(version);
+        }
+        self.packages
+            .iter()
+            .find(|(key, _)| lookup(key))
+            .and_then(|(_, versions)| versions.iter().max_by_key(|v| version_key(v)))
+            .map(String::as_str)
+    }
+}
+
+// parse_locked_versions - this reads a Cargo.lock for version resolution.
+// A member's `dependencies` entry carries the version ("serde 1.0.210") only
+// when several are locked, otherwise the package's single version applies
+pub fn parse_locked_versions(text: &str) -> Result<LockedVersions> {
+    let lock: Table = toml::from_str(text).context("failed to parse Cargo.lock")?;
+    let mut locked = LockedVersions::default();
+    let mut member_deps = Vec::new();
+
+    for package in lock
+        .get("package")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let (Some(name), Some(version)) = (
+            package.get("name").and_then(Value::as_str),
+            package.get("version").and_then(Value::as_str),
+        ) else {
+            continue;
+        };
+        locked
+            .packages
+            .entry(name.to_string())
+            .or_default()
+            .push(version.to_string());
+
+        if package.get("source").is_none() {
+            member_deps.extend(
+                package
+                    .get("dependencies")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_str)
+                    .map(String::from),
+            );
+        }
+    }
+
+    for dep in member_deps {
+        let mut parts = dep.split_whitespace();
+        let Some(name) = parts.next() else {
+            continue;
+        };
+        let version = match parts.next() {
+            Some(version) => Some(version.to_string()),
+            None => locked
+                .packages
+                .get(name)
+                .filter(|versions| versions.len() == 1)
+                .map(|versions| versions[0].clone()),
+        };
+        if let Some(version) = version {
+            locked.direct.insert(name.to_string(), version);
+        }
+    }
+
+    Ok(locked)
+}
+
+fn version_key(version: &str) -> Vec<u64> {
+    version
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+// diff_dependencies - this compares two dependency sets crate by crate
+pub fn diff_dependencies(old: &Dependencies, new: &Dependencies) -> Vec<DependencyChange> {
+    let mut changes = Vec::new();
+
+    for ((section, crate_name), before) in old {
+        let key = (section.clone(), crate_name.clone());
+        let kind = match new.get(&key) {
+            None => DependencyChangeKind::Removed,
+            Some(after) if after.version != before.version => DependencyChangeKind::Bumped,
+            Some(after) if after.spec != before.spec => DependencyChangeKind::Changed,
+            Some(_) => continue,
+        };
+        changes.push(DependencyChange {
+            crate_name: crate_name.clone(),
+            section: section.clone(),
+            kind,
+            old_version: before.version.clone(),
+            new_version: new.get(&key).and_then(|d| d.version.clone()),
+        });
+    }
+
+    for ((section, crate_name), after) in new {
+        if !old.contains_key(&(section.clone(), crate_name.clone())) {
+            changes.push(DependencyChange {
+                crate_name: crate_name.clone(),
+                section: section.clone(),
+                kind: DependencyChangeKind::Added,
+                old_version: None,
+                new_version: after.version.clone(),
+            });
+        }
+    }
+
+    changes
+}
+
+// dependency_diff - this picks the right parser for `file_name` and diffs the
+// two versions. Returns None for files that aren't manifests or lockfiles, or
+// when either side doesn't parse (mid-edit saves are common)
+pub fn dependency_diff(file_name: &str, old: &str, new: &str) -> Option<Vec<DependencyChange>> {
+    let parse = match file_name {
+        "Cargo.toml" => parse_manifest,
+        "Cargo.lock" => parse_lockfile,
+        _ => return None,
+    };
+
+    // a brand new manifest has nothing to diff against
+    let old = if old.is_empty() {
+        Dependencies::new()
+    } else {
+        parse(old).ok()?
+    };
+    let new = parse(new).ok()?;
+
+    Some(diff_dependencies(&old, &new))
+}
@@ -0,0 +1,737 @@
+use anyhow::{bail, Context, Result};
+use rusqlite::Connection;
+
+/// One step in the schema history. `version` is the `PRAGMA user_version` the
+/// database is at once `sql` has been applied.
+pub struct Migration {
+    pub version: i64,
+    pub description: &'static str,
+    pub sql: &'static str,
+}
+
+// MIGRATIONS - append only. Never edit a migration that has shipped; add a new
+// one instead. Versions 1-3 use IF NOT EXISTS because databases created before
+// migrations existed already have some of these tables at user_version 0
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "scaffolds and file changes",
+        sql: r##"
+            CREATE TABLE IF NOT EXISTS scaffolds (
+                id INTEGER PRIMARY KEY,
+                description TEXT NOT NULL,
+                content TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS file_changes (
+                id INTEGER PRIMARY KEY,
+                file_path TEXT NOT NULL,
+                hunk_idx INTEGER NOT NULL,
+                change_id TEXT NOT NULL,
+                old_start INTEGER NOT NULL,
+                old_count INTEGER NOT NULL,
+                new_start INTEGER NOT NULL,
+                new_count INTEGER NOT NULL,
+                before_lines TEXT NOT NULL,
+                after_lines TEXT NOT NULL,
+                changed_at TEXT NOT NULL
+            );
+        "##,
+    },
+    Migration {
+        version: 2,
+        description: "api call usage",
+        sql: r##"
+            CREATE TABLE IF NOT EXISTS api_calls (
+                id INTEGER PRIMARY KEY,
+                tool TEXT NOT NULL,
+                backend TEXT NOT NULL,
+                model TEXT NOT NULL,
+                file_path TEXT,
+                input_tokens INTEGER NOT NULL,
+                output_tokens INTEGER NOT NULL,
+                latency_ms INTEGER NOT NULL,
+                created_at TEXT NOT NULL
+            );
+        "##,
+    },
+    Migration {
+        version: 3,
+        description: "saved reviews",
+        sql: r##"
+            CREATE TABLE IF NOT EXISTS reviews (
+                id INTEGER PRIMARY KEY,
+                file_path TEXT NOT NULL,
+                content_hash TEXT NOT NULL,
+                review TEXT NOT NULL,
+                backend TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            );
+        "##,
+    },
+    Migration {
+        version: 4,
+        description: "full-text index over scaffolds",
+        sql: r##"
+            CREATE VIRTUAL TABLE scaffolds_fts USING fts5(
+                description,
+                content,
+                content = 'scaffolds',
+                content_rowid = 'id',
+                tokenize = 'porter unicode61'
+            );
+
+            CREATE TRIGGER scaffolds_fts_insert AFTER INSERT ON scaffolds BEGIN
+                INSERT INTO scaffolds_fts (rowid, description, content)
+                VALUES (new.id, new.description, new.content);
+            END;
+
+            CREATE TRIGGER scaffolds_fts_delete AFTER DELETE ON scaffolds BEGIN
+                INSERT INTO scaffolds_fts (scaffolds_fts, rowid, description, content)
+                VALUES ('delete', old.id, old.description, old.content);
+            END;
+
+            CREATE TRIGGER scaffolds_fts_update AFTER UPDATE ON scaffolds BEGIN
+                INSERT INTO scaffolds_fts (scaffolds_fts, rowid, description, content)
+                VALUES ('delete', old.id, old.description, old.content);
+                INSERT INTO scaffolds_fts (rowid, description, content)
+                VALUES (new.id, new.description, new.content);
+            END;
+
+            -- index the scaffolds saved before this migration
+            INSERT INTO scaffolds_fts (scaffolds_fts) VALUES ('rebuild');
+        "##,
+    },
+    Migration {
+        version: 5,
+        description: "dependency changes from Cargo.toml and Cargo.lock",
+        sql: r##"
+            CREATE TABLE dependency_changes (
+                id INTEGER PRIMARY KEY,
+                change_id TEXT NOT NULL,
+                file_path TEXT NOT NULL,
+                crate_name TEXT NOT NULL,
+                section TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                old_version TEXT,
+                new_version TEXT,
+                changed_at TEXT NOT NULL
+            );
+
+            CREATE INDEX dependency_changes_change_id ON dependency_changes (change_id);
+        "##,
+    },
+    Migration {
+        version: 6,
+        description: "create, delete and rename events in file changes",
+        sql: r##"
+            ALTER TABLE file_changes ADD COLUMN kind TEXT NOT NULL DEFAULT 'modify';
+            ALTER TABLE file_changes ADD COLUMN old_path TEXT;
+        "##,
+    },
+    Migration {
+        version: 7,
+        description: "file snapshots for point-in-time reconstruction",
+        sql: r##"
+            -- exact text of each hunk's new range and the hash of the whole
+            -- file after the save; NULL for hunks recorded before this
+            ALTER TABLE file_changes ADD COLUMN new_text TEXT;
+            ALTER TABLE file_changes ADD COLUMN content_hash TEXT;
+            CREATE INDEX file_changes_path_time ON file_changes (file_path, changed_at);
+
+            CREATE TABLE file_snapshots (
+                id INTEGER PRIMARY KEY,
+                file_path TEXT NOT NULL,
+                change_id TEXT,
+                reason TEXT NOT NULL,
+                content TEXT NOT NULL,
+                content_hash TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            );
+            CREATE INDEX file_snapshots_path_time ON file_snapshots (file_path, created_at);
+        "##,
+    },
+    Migration {
+        version: 8,
+        description: "coding sessions",
+        sql: r##"
+            CREATE TABLE sessions (
+                id INTEGER PRIMARY KEY,
+                started_at TEXT NOT NULL,
+                ended_at TEXT NOT NULL,
+                save_count INTEGER NOT NULL DEFAULT 0,
+                lines_added INTEGER NOT NULL DEFAULT 0,
+                lines_removed INTEGER NOT NULL DEFAULT 0
+            );
+
+            ALTER TABLE file_changes ADD COLUMN lines_added INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE file_changes ADD COLUMN lines_removed INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE file_changes ADD COLUMN session_id INTEGER REFERENCES sessions (id);
+            ALTER TABLE dependency_changes ADD COLUMN session_id INTEGER REFERENCES sessions (id);
+            CREATE INDEX file_changes_session ON file_changes (session_id);
+            CREATE INDEX dependency_changes_session ON dependency_changes (session_id);
+
+            -- group the saves recorded so far using the default 30 minute idle
+            -- gap. Line counts weren't kept for them, so their churn stays 0
+            CREATE TEMP TABLE save_sessions AS
+            WITH saves AS (
+                SELECT change_id, MIN(changed_at) AS at FROM file_changes GROUP BY change_id
+                UNION ALL
+                SELECT change_id, MIN(changed_at) FROM dependency_changes GROUP BY change_id
+            ),
+            gaps AS (
+                SELECT change_id, at,
+                       CASE
+                           WHEN julianday(at) - julianday(LAG(at) OVER (ORDER BY at)) <= 30.0 / 1440
+                           THEN 0 ELSE 1
+                       END AS new_session
+                FROM saves
+            )
+            SELECT change_id, at,
+                   SUM(new_session) OVER (ORDER BY at ROWS UNBOUNDED PRECEDING) AS session_id
+            FROM gaps;
+
+            INSERT INTO sessions (id, started_at, ended_at, save_count)
+            SELECT session_id, MIN(at), MAX(at), COUNT(*) FROM save_sessions GROUP BY session_id;
+
+            UPDATE file_changes SET session_id = (
+                SELECT session_id FROM save_sessions s WHERE s.change_id = file_changes.change_id
+            );
+            UPDATE dependency_changes SET session_id = (
+                SELECT session_id FROM save_sessions s WHERE s.change_id = dependency_changes.change_id
+            );
+
+            DROP TABLE save_sessions;
+        "##,
+    },
+    Migration {
+        version: 9,
+        description: "compiler diagnostics",
+        sql: r##"
+            CREATE TABLE diagnostics (
+                id INTEGER PRIMARY KEY,
+                change_id TEXT NOT NULL,
+                session_id INTEGER REFERENCES sessions (id),
+                level TEXT NOT NULL,
+                code TEXT,
+                message TEXT NOT NULL,
+                file_path TEXT,
+                line_start INTEGER,
+                column_start INTEGER,
+                line_end INTEGER,
+                rendered TEXT,
+                created_at TEXT NOT NULL
+            );
+            CREATE INDEX diagnostics_change ON diagnostics (change_id);
+            CREATE INDEX diagnostics_time ON diagnostics (created_at);
+        "##,
+    },
+    Migration {
+        version: 10,
+        description: "clippy runs",
+        sql: r##"
+            CREATE TABLE clippy_runs (
+                id INTEGER PRIMARY KEY,
+                crate_root TEXT NOT NULL,
+                finding_count INTEGER NOT NULL,
+                ran_at TEXT NOT NULL
+            );
+
+            -- clippy findings come from a run rather than a save, so change_id
+            -- becomes optional; SQLite can only do that by rebuilding the table
+            CREATE TABLE diagnostics_new (
+                id INTEGER PRIMARY KEY,
+                change_id TEXT,
+                session_id INTEGER REFERENCES sessions (id),
+                source TEXT NOT NULL DEFAULT 'check',
+                run_id INTEGER REFERENCES clippy_runs (id),
+                level TEXT NOT NULL,
+                code TEXT,
+                message TEXT NOT NULL,
+                file_path TEXT,
+                line_start INTEGER,
+                column_start INTEGER,
+                line_end INTEGER,
+                rendered TEXT,
+                created_at TEXT NOT NULL
+            );
+            INSERT INTO diagnostics_new
+                (id, change_id, session_id, level, code, message, file_path, line_start, column_start, line_end, rendered, created_at)
+            SELECT id, change_id, session_id, level, code, message, file_path, line_start, column_start, line_end, rendered, created_at
+            FROM diagnostics;
+            DROP TABLE diagnostics;
+            ALTER TABLE diagnostics_new RENAME TO diagnostics;
+
+            CREATE INDEX diagnostics_change ON diagnostics (change_id);
+            CREATE INDEX diagnostics_time ON diagnostics (created_at);
+            CREATE INDEX diagnostics_run ON diagnostics (run_id);
+        "##,
+    },
+    Migration {
+        version: 11,
+        description: "rustc error explanations",
+        sql: r##"
+            CREATE TABLE error_explanations (
+                code TEXT PRIMARY KEY,
+                explanation TEXT NOT NULL,
+                rustc_version TEXT,
+                fetched_at TEXT NOT NULL
+            );
+            CREATE INDEX diagnostics_code ON diagnostics (code, created_at);
+        "##,
+    },
+    Migration {
+        version: 12,
+        description: "check runs per save",
+        sql: r##"
+            CREATE TABLE check_runs (
+                id INTEGER PRIMARY KEY,
+                change_id TEXT NOT NULL,
+                session_id INTEGER REFERENCES sessions (id),
+                source TEXT NOT NULL,
+                error_count INTEGER NOT NULL,
+                warning_count INTEGER NOT NULL,
+                ran_at TEXT NOT NULL
+            );
+            CREATE INDEX check_runs_time ON check_runs (ran_at);
+
+            -- saves checked so far only left a trace if something was reported
+            INSERT INTO check_runs (change_id, session_id, source, error_count, warning_count, ran_at)
+            SELECT change_id, MAX(session_id), source, SUM(level = 'error'), SUM(level = 'warning'), MIN(created_at)
+            FROM diagnostics
+            WHERE change_id IS NOT NULL
+            GROUP BY change_id, source;
+        "##,
+    },
+    Migration {
+        version: 13,
+        description: "http cache",
+        sql: r##"
+            CREATE TABLE http_cache (
+                url TEXT PRIMARY KEY,
+                crate_name TEXT NOT NULL,
+                version TEXT NOT NULL,
+                body TEXT NOT NULL,
+                etag TEXT,
+                last_modified TEXT,
+                fetched_at TEXT NOT NULL,
+                expires_at TEXT NOT NULL
+            );
+            CREATE INDEX http_cache_crate ON http_cache (crate_name, version);
+        "##,
+    },
+    Migration {
+        version: 14,
+        description: "local doc index",
+        sql: r##"
+            CREATE TABLE doc_items (
+                id INTEGER PRIMARY KEY,
+                crate_name TEXT NOT NULL,
+                version TEXT,
+                path TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                location TEXT NOT NULL,
+                source TEXT NOT NULL,
+                docs TEXT,
+                indexed_at TEXT NOT NULL
+            );
+            CREATE INDEX doc_items_crate ON doc_items (crate_name);
+        "##,
+    },
+    Migration {
+        version: 15,
+        description: "compiler diagnostics from clippy runs are check diagnostics",
+        sql: r##"
+            UPDATE diagnostics
+            SET source = 'check'
+            WHERE source = 'clippy' AND (code IS NULL OR code NOT LIKE 'clippy::%');
+        "##,
+    },
+];
+
+// latest_version - the version a fully migrated database ends up at
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map_or(0, |m| m.version)
+}
+
+pub fn schema_version(conn: &Connection) -> Result<i64> {
+    conn.pragma_query_value(None, "user_version", |row| row.get(0))
+        .context("failed to read schema version")
+}
+
+// migrate - this applies every migration newer than the database's
+// user_version, each in its own transaction together with the version bump,
+// so a failed step leaves the database at the last good version
+pub fn migrate(conn: &mut Connection) -> Result<()> {
+    migrate_to(conn, latest_version())
+}
+
+pub fn migrate_to(conn: &mut Connection, target: i64) -> Result<()> {
+    let current = schema_version(conn)?;
+    if current > latest_version() {
+        bail!(
+            "database schema version {current} is newer than this build supports ({}); \
+             upgrade rust-tutor-mcp",
+            latest_version()
+        );
+    }
+
+    for migration in MIGRATIONS
+        .iter()
+        .filter(|m| m.version > current && m.version <= target)
+    {
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration.sql).with_context(|| {
+            format!(
+                "migration {} ({}) failed",
+                migration.version, migration.description
+            )
+        })?;
+        tx.pragma_update(None, "user_version", migration.version)?;
+        tx.commit()?;
+
+        tracing::debug!(
+            version = migration.version,
+            "applied migration: {}",
+            migration.description
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the schema open_at created before migrations existed, minus the tables
+    // that were added later
+    const LEGACY_SCAFFOLDS: &str = r##"
+        CREATE TABLE scaffolds (
+            id INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            content TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        CREATE TABLE file_changes (
+            id INTEGER PRIMARY KEY,
+            file_path TEXT NOT NULL,
+            hunk_idx INTEGER NOT NULL,
+            change_id TEXT NOT NULL,
+            old_start INTEGER NOT NULL,
+            old_count INTEGER NOT NULL,
+            new_start INTEGER NOT NULL,
+            new_count INTEGER NOT NULL,
+            before_lines TEXT NOT NULL,
+            after_lines TEXT NOT NULL,
+            changed_at TEXT NOT NULL
+
+        );
+    "##;
+
+    const LEGACY_USAGE: &str = r##"
+        CREATE TABLE api_calls (
+            id INTEGER PRIMARY KEY,
+            tool TEXT NOT NULL,
+            backend TEXT NOT NULL,
+            model TEXT NOT NULL,
+            file_path TEXT,
+            input_tokens INTEGER NOT NULL,
+            output_tokens INTEGER NOT NULL,
+            latency_ms INTEGER NOT NULL,
+            created_at TEXT NOT NULL
+        );
+    "##;
+
+    fn seed_scaffold(conn: &Connection) {
+        conn.execute(
+            "INSERT INTO scaffolds (description, content, created_at) VALUES ('cli', 'plan', '2025-01-01')",
+            [],
+        )
+        .unwrap();
+    }
+
+    fn tables(conn: &Connection) -> Vec<String> {
+        let mut stmt = conn
+            .prepare("SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name")
+            .unwrap();
+        stmt.query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap()
+    }
+
+    fn count(conn: &Connection, table: &str) -> i64 {
+        conn.query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+            row.get(0)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn versions_are_strictly_increasing() {
+        for pair in MIGRATIONS.windows(2) {
+            assert_eq!(pair[1].version, pair[0].version + 1);
+        }
+        assert_eq!(MIGRATIONS[0].version, 1);
+    }
+
+    #[test]
+    fn fresh_database_reaches_latest() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+
+        assert_eq!(schema_version(&conn).unwrap(), latest_version());
+        for table in [
+            "scaffolds",
+            "scaffolds_fts",
+            "file_changes",
+            "dependency_changes",
+            "file_snapshots",
+            "sessions",
+            "diagnostics",
+            "clippy_runs",
+            "error_explanations",
+            "check_runs",
+            "http_cache",
+            "doc_items",
+            "api_calls",
+            "reviews",
+        ] {
+            assert!(tables(&conn).iter().any(|t| t == table), "missing {table}");
+        }
+    }
+
+    #[test]
+    fn migrating_twice_is_a_no_op() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+        seed_scaffold(&conn);
+        migrate(&mut conn).unwrap();
+
+        assert_eq!(schema_version(&conn).unwrap(), latest_version());
+        assert_eq!(count(&conn, "scaffolds"), 1);
+    }
+
+    #[test]
+    fn upgrades_from_every_historic_version() {
+        for from in 0..latest_version() {
+            let mut conn = Connection::open_in_memory().unwrap();
+            migrate_to(&mut conn, from).unwrap();
+            assert_eq!(schema_version(&conn).unwrap(), from);
+            if from >= 1 {
+                seed_scaffold(&conn);
+            }
+
+            migrate(&mut conn).unwrap();
+
+            assert_eq!(
+                schema_version(&conn).unwrap(),
+                latest_version(),
+                "from v{from}"
+            );
+            if from >= 1 {
+                assert_eq!(count(&conn, "scaffolds"), 1, "from v{from}");
+            }
+        }
+    }
+
+    #[test]
+    fn upgrades_unversioned_legacy_databases() {
+        for legacy in [
+            LEGACY_SCAFFOLDS.to_string(),
+            format!("{LEGACY_SCAFFOLDS}{LEGACY_USAGE}"),
+        ] {
+            let mut conn = Connection::open_in_memory().unwrap();
+            conn.execute_batch(&legacy).unwrap();
+            seed_scaffold(&conn);
+            assert_eq!(schema_version(&conn).unwrap(), 0);
+
+            migrate(&mut conn).unwrap();
+
+            assert_eq!(schema_version(&conn).unwrap(), latest_version());
+            assert_eq!(count(&conn, "scaffolds"), 1);
+            assert_eq!(count(&conn, "reviews"), 0);
+        }
+    }
+
+    #[test]
+    fn scaffold_index_covers_old_and_new_rows() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate_to(&mut conn, 3).unwrap();
+        seed_scaffold(&conn);
+        migrate(&mut conn).unwrap();
+
+        conn.execute(
+            "INSERT INTO scaffolds (description, content, created_at) VALUES ('web server', 'use axum', '2025-01-02')",
+            [],
+        )
+        .unwrap();
+        let matches = |query: &str| -> i64 {
+            conn.query_row(
+                "SELECT COUNT(*) FROM scaffolds_fts WHERE scaffolds_fts MATCH ?1",
+                [query],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+        assert_eq!(matches("plan"), 1);
+        assert_eq!(matches("axum"), 1);
+
+        conn.execute("DELETE FROM scaffolds WHERE description = 'cli'", [])
+            .unwrap();
+        assert_eq!(matches("plan"), 0);
+    }
+
+    #[test]
+    fn existing_file_changes_become_modify_events() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate_to(&mut conn, 5).unwrap();
+        conn.execute(
+            r##"
+            INSERT INTO file_changes (file_path, hunk_idx, change_id, old_start, old_count, new_start, new_count, before_lines, after_lines, changed_at)
+            VALUES ('src/main.rs', 0, 'c1', 0, 1, 0, 1, 'a', 'b', '2025-01-01')
+            "##,
+            [],
+        )
+        .unwrap();
+        migrate(&mut conn).unwrap();
+
+        let (kind, old_path): (String, Option<String>) = conn
+            .query_row("SELECT kind, old_path FROM file_changes", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(kind, "modify");
+        assert_eq!(old_path, None);
+    }
+
+    #[test]
+    fn existing_saves_are_grouped_into_sessions() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate_to(&mut conn, 7).unwrap();
+        for (change_id, at) in [
+            ("a", "2025-01-01 09:00:00.000+00:00"),
+            ("b", "2025-01-01 09:20:00.000+00:00"),
+            ("c", "2025-01-01 11:00:00.000+00:00"),
+        ] {
+            conn.execute(
+                r##"
+                INSERT INTO file_changes (file_path, hunk_idx, change_id, old_start, old_count, new_start, new_count, before_lines, after_lines, changed_at)
+                VALUES ('src/lib.rs', 0, ?1, 0, 0, 0, 1, '', 'x', ?2)
+                "##,
+                [change_id, at],
+            )
+            .unwrap();
+        }
+        migrate(&mut conn).unwrap();
+
+        let sessions: Vec<(i64, String, String, i64)> = conn
+            .prepare("SELECT id, started_at, ended_at, save_count FROM sessions ORDER BY id")
+            .unwrap()
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].3, 2);
+        assert_eq!(sessions[1].1, "2025-01-01 11:00:00.000+00:00");
+
+        let unassigned = count(&conn, "file_changes WHERE session_id IS NULL");
+        assert_eq!(unassigned, 0);
+    }
+
+    #[test]
+    fn existing_diagnostics_keep_their_save() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate_to(&mut conn, 9).unwrap();
+        conn.execute(
+            r##"
+            INSERT INTO diagnostics (change_id, level, code, message, created_at)
+            VALUES ('abc', 'error', 'E0382', 'borrow of moved value', '2025-01-01 09:00:00+00:00')
+            "##,
+            [],
+        )
+        .unwrap();
+        migrate(&mut conn).unwrap();
+
+        let (change_id, source): (Option<String>, String) = conn
+            .query_row("SELECT change_id, source FROM diagnostics", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(change_id.as_deref(), Some("abc"));
+        assert_eq!(source, "check");
+    }
+
+    #[test]
+    fn checked_saves_become_check_runs() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate_to(&mut conn, 11).unwrap();
+        for (change_id, level) in [
+            ("a", "error"),
+            ("a", "warning"),
+            ("a", "error"),
+            ("b", "warning"),
+        ] {
+            conn.execute(
+                r##"
+                INSERT INTO diagnostics (change_id, level, message, created_at)
+                VALUES (?1, ?2, 'msg', '2025-01-01 09:00:00+00:00')
+                "##,
+                [change_id, level],
+            )
+            .unwrap();
+        }
+        migrate(&mut conn).unwrap();
+
+        let runs: Vec<(String, i64, i64)> = conn
+            .prepare(
+                "SELECT change_id, error_count, warning_count FROM check_runs ORDER BY change_id",
+            )
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(runs, vec![("a".to_string(), 2, 1), ("b".to_string(), 0, 1)]);
+    }
+
+    #[test]
+    fn compiler_diagnostics_from_clippy_become_check() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate_to(&mut conn, 14).unwrap();
+        for code in [Some("E0382"), Some("clippy::needless_return"), None] {
+            conn.execute(
+                r##"
+                INSERT INTO diagnostics (source, level, code, message, created_at)
+                VALUES ('clippy', 'error', ?1, 'msg', '2025-01-01 09:00:00+00:00')
+                "##,
+                [code],
+            )
+            .unwrap();
+        }
+        migrate(&mut conn).unwrap();
+
+        let sources: Vec<String> = conn
+            .prepare("SELECT source FROM diagnostics ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(sources, vec!["check", "clippy", "check"]);
+    }
+
+    #[test]
+    fn refuses_newer_databases() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", latest_version() + 1)
+            .unwrap();
+
+        assert!(migrate(&mut conn).is_err());
+    }
+}
@@ -0,0 +1,123 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    backend::{Completion, LlmError, ReviewBackend, Task, Usage},
+    config::{LlmConfig, ModelSettings},
+    retry::{send_with_retry, RetryPolicy},
+};
+
+const DEFAULT_HOST: &str = "http://localhost:11434";
+const DEFAULT_MODEL: &str = "qwen2.5-coder";
+
+/// Client for a local Ollama server using its native `/api/chat` endpoint.
+#[derive(Clone)]
+pub struct OllamaClient {
+    client: reqwest::Client,
+    host: String,
+    models: ModelSettings,
+    retry: RetryPolicy,
+}
+
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    stream: bool,
+    messages: Vec<ChatMessage<'a>>,
+    options: ChatOptions,
+}
+
+#[derive(Serialize)]
+struct ChatOptions {
+    num_predict: u32,
+}
+
+#[derive(Serialize)]
+struct ChatMessage<'a> {
+    role: &'static str,
+    content: &'a str,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    message: ResponseMessage,
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    prompt_eval_count: i64,
+    #[serde(default)]
+    eval_count: i64,
+}
+
+#[derive(Deserialize)]
+struct ResponseMessage {
+    content: String,
+}
+
+impl OllamaClient {
+    pub fn new(config: &LlmConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            host: config
+                .base_url_or_env("OLLAMA_HOST")
+                .unwrap_or_else(|| DEFAULT_HOST.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            models: config.models(DEFAULT_MODEL),
+            retry: RetryPolicy::from_config(config),
+        }
+    }
+}
+
+#[async_trait]
+impl ReviewBackend for OllamaClient {
+    fn name(&self) -> &'static str {
+        "ollama"
+    }
+
+    async fn complete(&self, task: Task, prompt: &str) -> Result<Completion, LlmError> {
+        let request = ChatRequest {
+            model: self.models.model_for(task),
+            stream: false,
+            options: ChatOptions {
+                num_predict: self.models.max_tokens,
+            },
+            messages: vec![
+                ChatMessage {
+                    role: "system",
+                    content: task.system_prompt(),
+                },
+                ChatMessage {
+                    role: "user",
+                    content: prompt,
+                },
+            ],
+        };
+
+        let response = send_with_retry(&self.retry, || {
+            self.client
+                .post(format!("{}/api/chat", self.host))
+                .json(&request)
+        })
+        .await?;
+
+        let chat_response: ChatResponse = response.json().await?;
+
+        if chat_response.message.content.is_empty() {
+            return Err(LlmError::InvalidResponse(
+                "Ollama returned empty response".into(),
+            ));
+        }
+
+        Ok(Completion {
+            text: chat_response.message.content,
+            model: chat_response
+                .model
+                .unwrap_or_else(|| request.model.to_string()),
+            usage: Usage {
+                input_tokens: chat_response.prompt_eval_count,
+                output_tokens: chat_response.eval_count,
+            },
+        })
+    }
+}
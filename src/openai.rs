@@ -0,0 +1,138 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    backend::{Completion, LlmError, ReviewBackend, Task, Usage},
+    config::{LlmConfig, ModelSettings},
+    retry::{send_with_retry, RetryPolicy},
+};
+
+const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+const DEFAULT_MODEL: &str = "gpt-4o-mini";
+
+/// Client for any server exposing the OpenAI `/chat/completions` API
+/// (OpenAI itself, vLLM, LM Studio, llama.cpp server, ...).
+#[derive(Clone)]
+pub struct OpenAiClient {
+    client: reqwest::Client,
+    api_key: Option<String>,
+    base_url: String,
+    models: ModelSettings,
+    retry: RetryPolicy,
+}
+
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    max_tokens: u32,
+    messages: Vec<ChatMessage<'a>>,
+}
+
+#[derive(Serialize)]
+struct ChatMessage<'a> {
+    role: &'static str,
+    content: &'a str,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<Choice>,
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    usage: Option<ChatUsage>,
+}
+
+#[derive(Deserialize)]
+struct ChatUsage {
+    #[serde(default)]
+    prompt_tokens: i64,
+    #[serde(default)]
+    completion_tokens: i64,
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    message: ChoiceMessage,
+}
+
+#[derive(Deserialize)]
+struct ChoiceMessage {
+    content: Option<String>,
+}
+
+impl OpenAiClient {
+    pub fn new(config: &LlmConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_key: config.api_key_or_env("OPENAI_API_KEY"),
+            base_url: config
+                .base_url_or_env("OPENAI_BASE_URL")
+                .unwrap_or_else(|| DEFAULT_BASE_URL.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            models: config.models(DEFAULT_MODEL),
+            retry: RetryPolicy::from_config(config),
+        }
+    }
+}
+
+#[async_trait]
+impl ReviewBackend for OpenAiClient {
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    async fn complete(&self, task: Task, prompt: &str) -> Result<Completion, LlmError> {
+        let request = ChatRequest {
+            model: self.models.model_for(task),
+            max_tokens: self.models.max_tokens,
+            messages: vec![
+                ChatMessage {
+                    role: "system",
+                    content: task.system_prompt(),
+                },
+                ChatMessage {
+                    role: "user",
+                    content: prompt,
+                },
+            ],
+        };
+
+        let response = send_with_retry(&self.retry, || {
+            let builder = self
+                .client
+                .post(format!("{}/chat/completions", self.base_url))
+                .json(&request);
+            match &self.api_key {
+                Some(key) => builder.bearer_auth(key),
+                None => builder,
+            }
+        })
+        .await?;
+
+        let chat_response: ChatResponse = response.json().await?;
+
+        let usage = chat_response
+            .usage
+            .map(|u| Usage {
+                input_tokens: u.prompt_tokens,
+                output_tokens: u.completion_tokens,
+            })
+            .unwrap_or_default();
+        let model = chat_response
+            .model
+            .unwrap_or_else(|| request.model.to_string());
+
+        let text = chat_response
+            .choices
+            .into_iter()
+            .next()
+            .and_then(|choice| choice.message.content)
+            .ok_or_else(|| {
+                LlmError::InvalidResponse("OpenAI-compatible API returned empty response".into())
+            })?;
+
+        Ok(Completion { text, model, usage })
+    }
+}
@@ -0,0 +1,162 @@
+use std::time::{Duration, Instant};
+
+use reqwest::{header::RETRY_AFTER, RequestBuilder, Response};
+
+use crate::{backend::LlmError, config::LlmConfig};
+
+/// How hard to try before giving up on an upstream LLM request.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Budget for the whole call, including every retry and reading the body
+    pub deadline: Duration,
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &LlmConfig) -> Self {
+        Self {
+            max_retries: config.max_retries,
+            initial_backoff: Duration::from_millis(config.initial_backoff_ms),
+            max_backoff: Duration::from_millis(config.max_backoff_ms),
+            deadline: Duration::from_secs(config.request_timeout_secs),
+        }
+    }
+
+    // backoff - exponential backoff with full jitter for the given retry attempt
+    fn backoff(&self, attempt: u32) -> Duration {
+        let cap = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff);
+        Duration::from_millis(rand::random_range(0..=cap.as_millis() as u64))
+    }
+}
+
+// send_with_retry - this sends the request built by `build` until it succeeds,
+// fails with a non-retryable status, runs out of retries or hits the deadline.
+// `build` is called once per attempt because a RequestBuilder can't be reused.
+pub async fn send_with_retry(
+    policy: &RetryPolicy,
+    build: impl Fn() -> RequestBuilder,
+) -> Result<Response, LlmError> {
+    let started = Instant::now();
+    let mut attempt = 0;
+
+    loop {
+        let remaining = policy
+            .deadline
+            .checked_sub(started.elapsed())
+            .filter(|d| !d.is_zero())
+            .ok_or(LlmError::Timeout(policy.deadline))?;
+
+        let error = match build().timeout(remaining).send().await {
+            Ok(response) if response.status().is_success() => return Ok(response),
+            Ok(response) => classify(response).await,
+            Err(e) if e.is_timeout() => return Err(LlmError::Timeout(policy.deadline)),
+            Err(e) => LlmError::Network(e),
+        };
+
+        if !error.is_retryable() || attempt >= policy.max_retries {
+            return Err(error);
+        }
+
+        // anything past the deadline gives up below either way
+        let delay = match error.retry_after() {
+            Some(retry_after) => retry_after.min(policy.deadline),
+            None => policy.backoff(attempt),
+        };
+        if started.elapsed() + delay >= policy.deadline {
+            return Err(error);
+        }
+
+        attempt += 1;
+        tracing::warn!(
+            attempt,
+            delay_ms = delay.as_millis() as u64,
+            "LLM request failed, retrying: {error}"
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
+// classify - this turns a non-2xx response into a typed error
+async fn classify(response: Response) -> LlmError {
+    let status = response.status();
+    let retry_after = response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_retry_after);
+    let message = error_message(&response.text().await.unwrap_or_default());
+
+    match status.as_u16() {
+        401 | 403 => LlmError::Auth { status, message },
+        429 => LlmError::RateLimited {
+            retry_after,
+            message,
+        },
+        503 | 529 => LlmError::Overloaded {
+            status,
+            retry_after,
+            message,
+        },
+        408 | 500..=599 => LlmError::Server { status, message },
+        _ => LlmError::BadRequest { status, message },
+    }
+}
+
+// parse_retry_after - `retry-after` is either delay-seconds or an HTTP date
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    if let Ok(secs) = value.trim().parse::<f64>() {
+        // rejects negative, NaN, infinite and out-of-range values
+        return Duration::try_from_secs_f64(secs).ok();
+    }
+
+    chrono::DateTime::parse_from_rfc2822(value.trim())
+        .ok()
+        .map(|at| {
+            (at.with_timezone(&chrono::Utc) - chrono::Utc::now())
+                .to_std()
+                .unwrap_or_default()
+        })
+}
+
+// error_message - this pulls the human-readable message out of an error body.
+// Anthropic and OpenAI use {"error": {"message": ..}}, Ollama uses {"error": ".."}
+fn error_message(body: &str) -> String {
+    let parsed = serde_json::from_str::<serde_json::Value>(body).ok();
+    let message = parsed.as_ref().and_then(|v| {
+        let error = v.get("error")?;
+        error
+            .get("message")
+            .and_then(|m| m.as_str())
+            .or_else(|| error.as_str())
+    });
+
+    match message {
+        Some(m) => m.to_string(),
+        None if body.len() > 500 => format!("{}…", &body[..body.floor_char_boundary(500)]),
+        None => body.to_string(),
+    }
+}
+
+impl LlmError {
+    fn is_retryable(&self) -> bool {
+        match self {
+            LlmError::RateLimited { .. } | LlmError::Overloaded { .. } => true,
+            LlmError::Server { .. } => true,
+            LlmError::Network(e) => e.is_connect() || e.is_request(),
+            _ => false,
+        }
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        match self {
+            LlmError::RateLimited { retry_after, .. }
+            | LlmError::Overloaded { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use std::{path::PathBuf, process::Command};
+
+use anyhow::{anyhow, bail, Result};
+
+// normalize_code - this accepts `E0502`, `e0502`, `0502` or `502` and
+// returns the canonical `E0502` form, or None if it can't be an error code
+pub fn normalize_code(code: &str) -> Option<String> {
+    let digits = code.trim();
+    let digits = digits.strip_prefix(['E', 'e']).unwrap_or(digits);
+    if digits.is_empty() || digits.len() > 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(format!("E{digits:0>4}"))
+}
+
+// explain_error - this runs `rustc --explain` for an error code, returning
+// the markdown explanation that ships with the toolchain
+pub fn explain_error(code: &str) -> Result<String> {
+    let output = Command::new("rustc")
+        .args(["--explain", code])
+        .output()
+        .map_err(|_| anyhow!("rustc is not installed"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = stderr.trim().trim_start_matches("error: ");
+        bail!("{message}");
+    }
+    String::from_utf8(output.stdout).map_err(|e| anyhow!(e))
+}
+
+// version - this gets the toolchain's `rustc --version` line
+pub fn version() -> Option<String> {
+    Command::new("rustc")
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .and_then(|o| String::from_utf8(o.stdout).ok())
+        .map(|v| v.trim().to_string())
+}
+
+// release - this is the toolchain's version number, e.g. `1.95.0` or
+// `1.97.0-nightly`
+pub fn release() -> Option<String> {
+    version()?.split_whitespace().nth(1).map(String::from)
+}
+
+// doc_channel - doc.rust-lang.org serves releases by number and the
+// pre-release channels by name
+pub fn doc_channel(release: &str) -> &str {
+    if release.contains("nightly") {
+        "nightly"
+    } else if release.contains("beta") {
+        "beta"
+    } else {
+        release
+    }
+}
+
+// docs_dir - this is where `rustup doc` keeps the toolchain's html docs, when
+// the rust-docs component is installed
+pub fn docs_dir() -> Option<PathBuf> {
+    Command::new("rustup")
+        .args(["doc", "--path"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .and_then(|o| String::from_utf8(o.stdout).ok())
+        .and_then(|index| Some(PathBuf::from(index.trim()).parent()?.to_path_buf()))
+        .filter(|dir| dir.join("std").is_dir())
+}
@@ -0,0 +1,81 @@
+use async_trait::async_trait;
+use rmcp::{
+    model::{CreateMessageRequestParams, SamplingMessage},
+    Peer, RoleServer,
+};
+
+use crate::backend::{Completion, LlmError, ReviewBackend, Task, Usage};
+
+/// Delegates completions to the MCP client's own LLM through
+/// `sampling/createMessage`, for when no API backend is configured.
+pub struct SamplingBackend {
+    peer: Peer<RoleServer>,
+    max_tokens: u32,
+}
+
+impl SamplingBackend {
+    // for_peer - this only returns a backend when the client advertised the
+    // sampling capability during initialization
+    pub fn for_peer(peer: &Peer<RoleServer>, max_tokens: u32) -> Option<Self> {
+        let supported = peer
+            .peer_info()
+            .is_some_and(|info| info.capabilities.sampling.is_some());
+
+        supported.then(|| Self {
+            peer: peer.clone(),
+            max_tokens,
+        })
+    }
+}
+
+#[async_trait]
+impl ReviewBackend for SamplingBackend {
+    fn name(&self) -> &'static str {
+        "sampling"
+    }
+
+    async fn complete(&self, task: Task, prompt: &str) -> Result<Completion, LlmError> {
+        let params = CreateMessageRequestParams {
+            meta: None,
+            task: None,
+            messages: vec![SamplingMessage::user_text(prompt)],
+            model_preferences: None,
+            system_prompt: Some(task.system_prompt().to_string()),
+            include_context: None,
+            temperature: None,
+            max_tokens: self.max_tokens,
+            stop_sequences: None,
+            metadata: None,
+            tools: None,
+            tool_choice: None,
+        };
+
+        let result = self
+            .peer
+            .create_message(params)
+            .await
+            .map_err(|e| LlmError::Sampling(e.to_string()))?;
+
+        let text = result
+            .message
+            .content
+            .into_vec()
+            .into_iter()
+            .filter_map(|c| c.as_text().map(|t| t.text.clone()))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if text.is_empty() {
+            return Err(LlmError::InvalidResponse(
+                "client returned no text from sampling".into(),
+            ));
+        }
+
+        // the host doesn't report token counts for sampled messages
+        Ok(Completion {
+            text,
+            model: result.model,
+            usage: Usage::default(),
+        })
+    }
+}
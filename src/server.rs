@@ -1,47 +1,87 @@
 use std::{
+    path::Path,
     sync::{Arc, Mutex},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use rmcp::{
     handler::server::{router::tool::ToolRouter, wrapper::Parameters},
-    model::{CallToolResult, Content, ServerCapabilities, ServerInfo},
-    tool, tool_handler, tool_router, ErrorData as McpError, ServerHandler,
+    model::{CallToolResult, Content, ProgressNotificationParam, ServerCapabilities, ServerInfo},
+    service::RequestContext,
+    tool, tool_handler, tool_router, ErrorData as McpError, RoleServer, ServerHandler,
 };
 
 use crate::{
-    claude::{ClaudeClient, SCAFFOLD_PROMPT, SYSTEM_PROMPT},
-    docs_rs::fetch_docs,
-    man,
-    store::{FileChangeRecord, SaveEventSummary, ScaffoldRecord, TutorStore},
+    backend::{Completion, ReviewBackend, Task},
+    cargo, changes,
+    config::Config,
+    doc_index,
+    docs_rs::{self, Detail, Freshness},
+    history::{self, FileVersion},
+    learning, man, manifest,
+    prompts::{CHANGES_PROMPT, SCAFFOLD_PROMPT, SYSTEM_PROMPT},
+    rustc,
+    sampling::SamplingBackend,
+    store::{
+        ApiCallRecord, DependencyChangeRecord, DiagnosticRecord, ErrorExplanation,
+        FileChangeRecord, ReviewRecord, SaveEventSummary, ScaffoldMatch, ScaffoldRecord,
+        SessionRecord, TutorStore, UsageGrouping,
+    },
     tools::{
-        CheckCrateDocsParams, GetChangesByChangeIdParams, GetFileChangesParams, GetManPageParams,
-        GetScaffoldParams, ListRecentChangesParams, ListScaffoldsParams, ReviewFileParams,
-        SaveScaffoldParams, ScaffoldParams,
+        BuildDocIndexParams, CheckCrateDocsParams, DiffBetweenParams, ExplainErrorParams,
+        GetChangesByChangeIdParams, GetFileAtParams, GetFileChangesParams, GetManPageParams,
+        GetReviewParams, GetScaffoldParams, GetSessionParams, LearningReportParams,
+        ListDependenciesParams, ListRecentChangesParams, ListReviewsParams, ListScaffoldsParams,
+        ListSessionsParams, RecentDiagnosticsParams, ReviewChangesParams, ReviewFileParams,
+        RunClippyParams, SaveScaffoldParams, ScaffoldParams, SearchLocalDocsParams,
+        UsageReportParams,
     },
-    watcher::FileWatcher,
+    usage::{self, Pricing},
+    watcher::{self, FileWatcher},
 };
 
 #[derive(Clone)]
 pub struct RustTutor {
     tool_router: ToolRouter<Self>,
     store: Arc<Mutex<TutorStore>>,
-    claude: Option<ClaudeClient>,
+    backend: Option<Arc<dyn ReviewBackend>>,
+    pricing: Arc<Pricing>,
+    // token budget for sampling requests delegated to the host
+    max_tokens: u32,
     client: reqwest::Client,
 }
 
 const DEFAULT_LIST_LIMIT: i64 = 5;
+const MAX_LIST_LIMIT: i64 = 50;
+const DEFAULT_USAGE_DAYS: i64 = 30;
+// how far back history windows reach, about ten years
+const MAX_HISTORY_DAYS: i64 = 3650;
+const DEFAULT_CONTEXT_LINES: usize = 5;
+const MAX_CONTEXT_LINES: usize = 50;
+const DEFAULT_DIAGNOSTIC_DAYS: i64 = 7;
+const DEFAULT_REPORT_WEEKS: i64 = 4;
+const MAX_REPORT_WEEKS: i64 = 520;
+// other matches listed after the best one in doc lookups
+const DEFAULT_DOC_MATCHES: i64 = 10;
+// minimum gap between progress notifications while a review streams in
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
 
 #[tool_router]
 impl RustTutor {
-    pub fn new(claude: Option<ClaudeClient>) -> anyhow::Result<Self> {
+    pub fn new(config: &Config, backend: Option<Arc<dyn ReviewBackend>>) -> anyhow::Result<Self> {
         let store = Arc::new(Mutex::new(TutorStore::open()?));
 
-        FileWatcher::spawn(Arc::clone(&store));
+        FileWatcher::spawn(
+            Arc::clone(&store),
+            config.watcher.clone(),
+            config.cargo_check.clone(),
+        );
         Ok(Self {
             tool_router: Self::tool_router(),
             store,
-            claude,
+            backend,
+            pricing: Arc::new(Pricing::new(config.pricing.clone())),
+            max_tokens: config.llm.max_tokens,
             client: reqwest::Client::builder()
                 .timeout(Duration::from_secs(10))
                 .user_agent("Rust Tutor MCP")
@@ -57,28 +97,129 @@ impl RustTutor {
     async fn review_file(
         &self,
         Parameters(params): Parameters<ReviewFileParams>,
+        ctx: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, McpError> {
         let contents = tokio::fs::read_to_string(&params.file_path)
             .await
             .map_err(|e| McpError::internal_error(format!("Failed to read file: {e}"), None))?;
 
-        match &self.claude {
-            Some(client) => {
-                let review = client.review(&contents).await.map_err(|e| {
-                    McpError::internal_error(format!("Claude API error: {e}"), None)
-                })?;
-                Ok(CallToolResult::success(vec![Content::text(review)]))
-            }
-            None => {
-                // No API key — return file contents with review instructions
-                // so the host LLM (e.g. Claude Code) performs the review itself.
-                let response = format!(
-                    "{SYSTEM_PROMPT}\n\n---\n\n**File: `{}`**\n\n```rust\n{contents}\n```",
-                    params.file_path
-                );
-                Ok(CallToolResult::success(vec![Content::text(response)]))
+        // ground the review in real lint output when the file is in a cargo project
+        let clippy = self
+            .clippy_for_review(&params.file_path, &ctx)
+            .await?
+            .map(|findings| clippy_section(&findings))
+            .unwrap_or_default();
+
+        if let Some(text) = self
+            .run_review(
+                &ctx,
+                "review_file",
+                Task::Review,
+                &format!("{contents}{clippy}"),
+                &[(params.file_path.clone(), history::content_hash(&contents))],
+            )
+            .await?
+        {
+            return Ok(CallToolResult::success(vec![Content::text(text)]));
+        }
+
+        // No backend and no sampling — return file contents with review
+        // instructions so the host LLM (e.g. Claude Code) performs the review itself.
+        let response = format!(
+            "{SYSTEM_PROMPT}\n\n---\n\n**File: `{}`**\n\n```rust\n{contents}\n```{clippy}",
+            params.file_path
+        );
+        Ok(CallToolResult::success(vec![Content::text(response)]))
+    }
+
+    #[tool(
+        name = "review_changes",
+        description = "Review only the hunks from a recorded change ID or time range (defaults to the latest save), with surrounding context",
+        annotations(title = "Review Changes")
+    )]
+    async fn review_changes(
+        &self,
+        Parameters(params): Parameters<ReviewChangesParams>,
+        ctx: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        if params.until.is_some() && params.since.is_none() && params.change_id.is_none() {
+            return Err(McpError::invalid_params("`until` requires `since`", None));
+        }
+
+        let changes = {
+            let store = self.store.lock().expect("store lock poisoned");
+            match (&params.change_id, &params.since) {
+                (Some(change_id), _) => store.get_changes_for_change_id(change_id),
+                (None, Some(since)) => {
+                    let since = parse_time(since)?;
+                    let until = match &params.until {
+                        Some(until) => parse_time(until)?,
+                        None => chrono::Utc::now(),
+                    };
+                    store.get_changes_between(since, until)
+                }
+                (None, None) => store
+                    .latest_file_change_id()
+                    .and_then(|latest| match latest {
+                        Some(change_id) => store.get_changes_for_change_id(&change_id),
+                        None => Ok(Vec::new()),
+                    }),
             }
+            .map_err(|e| {
+                McpError::internal_error(format!("Failed to get file changes: {e}"), None)
+            })?
+        };
+
+        if changes.is_empty() {
+            // a Cargo.toml or Cargo.lock save has dependency changes but no hunks
+            let dependency_changes = match &params.change_id {
+                Some(change_id) => self
+                    .store
+                    .lock()
+                    .expect("store lock poisoned")
+                    .get_dependency_changes_for_change_id(change_id)
+                    .map_err(|e| {
+                        McpError::internal_error(
+                            format!("Failed to get dependency changes: {e}"),
+                            None,
+                        )
+                    })?,
+                None => Vec::new(),
+            };
+            let text = if dependency_changes.is_empty() {
+                "No file changes found".to_string()
+            } else {
+                format!(
+                    "This change only touched dependencies, so there is no code to review:\n\n{}",
+                    join_or_empty(
+                        &dependency_changes,
+                        "",
+                        DependencyChangeRecord::format_change
+                    )
+                )
+            };
+            return Ok(CallToolResult::success(vec![Content::text(text)]));
+        }
+
+        let diff = changes::build_changes_prompt(
+            &self.store.lock().expect("store lock poisoned"),
+            &changes,
+            params
+                .context_lines
+                .unwrap_or(DEFAULT_CONTEXT_LINES)
+                .min(MAX_CONTEXT_LINES),
+        );
+        let files = changes::reviewed_files(&changes);
+
+        if let Some(text) = self
+            .run_review(&ctx, "review_changes", Task::ReviewChanges, &diff, &files)
+            .await?
+        {
+            return Ok(CallToolResult::success(vec![Content::text(text)]));
         }
+
+        let response = format!("{CHANGES_PROMPT}\n\n---\n\n{diff}");
+        Ok(CallToolResult::success(vec![Content::text(response)]))
     }
 
     #[tool(
@@ -89,34 +230,41 @@ impl RustTutor {
     async fn scaffold(
         &self,
         Parameters(params): Parameters<ScaffoldParams>,
+        ctx: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, McpError> {
-        match &self.claude {
-            Some(client) => {
-                let mut plan = client.scaffold(&params.description).await.map_err(|e| {
-                    McpError::internal_error(format!("Claude API error: {e}"), None)
-                })?;
+        if let Some(backend) = self.backend_for(&ctx) {
+            let started = Instant::now();
+            match backend.scaffold(&params.description).await {
+                Ok(completion) => {
+                    self.record_api_call("scaffold", None, backend.name(), &completion, started);
 
-                let id = self
-                    .store
-                    .lock()
-                    .expect("store lock poisoned")
-                    .save_scaffold(&params.description, &plan)
-                    .map_err(|e| {
-                        McpError::internal_error(format!("Failed to save scaffold: {e}"), None)
-                    })?;
+                    let mut plan = completion.text;
 
-                plan.push_str(&format!("\n\n**ID**: {id}"));
+                    let id = self
+                        .store
+                        .lock()
+                        .expect("store lock poisoned")
+                        .save_scaffold(&params.description, &plan)
+                        .map_err(|e| {
+                            McpError::internal_error(format!("Failed to save scaffold: {e}"), None)
+                        })?;
 
-                Ok(CallToolResult::success(vec![Content::text(plan)]))
-            }
-            None => {
-                let response = format!(
-                    "{SCAFFOLD_PROMPT}\n\n---\n\n**Project description:**\n\n{}",
-                    params.description
-                );
-                Ok(CallToolResult::success(vec![Content::text(response)]))
+                    plan.push_str(&format!("\n\n**ID**: {id}"));
+
+                    return Ok(CallToolResult::success(vec![Content::text(plan)]));
+                }
+                Err(e) if self.backend.is_none() => {
+                    tracing::warn!("sampling failed, returning scaffold prompt instead: {e}");
+                }
+                Err(e) => return Err(e.into_mcp_error(backend.name())),
             }
         }
+
+        let response = format!(
+            "{SCAFFOLD_PROMPT}\n\n---\n\n**Project description:**\n\n{}",
+            params.description
+        );
+        Ok(CallToolResult::success(vec![Content::text(response)]))
     }
 
     #[tool(
@@ -141,27 +289,47 @@ impl RustTutor {
 
     #[tool(
         name = "list_scaffolds",
-        description = "List scaffolds, if no query then list the most recent",
+        description = "Search scaffolds by description and content (ranked, with highlighted snippets); if no query then list the most recent",
         annotations(title = "List Scaffolds")
     )]
     async fn list_scaffolds(
         &self,
         Parameters(params): Parameters<ListScaffoldsParams>,
     ) -> Result<CallToolResult, McpError> {
-        let records = {
+        let limit = params
+            .limit
+            .unwrap_or(DEFAULT_LIST_LIMIT)
+            .clamp(1, MAX_LIST_LIMIT);
+        let offset = params.offset.unwrap_or(0).max(0);
+
+        let (text, count) = {
             let store = self.store.lock().expect("store lock poisoned");
             match params.query {
-                Some(q) => store.search_scaffolds(&q),
-                None => store.list_recent_scaffolds(params.limit.unwrap_or(DEFAULT_LIST_LIMIT)),
+                Some(q) => store.search_scaffolds(&q, limit, offset).map(|matches| {
+                    let text =
+                        join_or_empty(&matches, "No scaffolds found", ScaffoldMatch::format_match);
+                    (text, matches.len())
+                }),
+                None => store.list_recent_scaffolds(limit, offset).map(|records| {
+                    let text = join_or_empty(
+                        &records,
+                        "No scaffolds found",
+                        ScaffoldRecord::format_changes,
+                    );
+                    (text, records.len())
+                }),
             }
             .map_err(|e| McpError::internal_error(format!("Failed to list scaffolds: {e}"), None))?
         };
 
-        let text = join_or_empty(
-            &records,
-            "No scaffolds found",
-            ScaffoldRecord::format_changes,
-        );
+        let text = if count as i64 == limit {
+            format!(
+                "{text}\n\n_More may be available with `offset: {}`_",
+                offset + limit
+            )
+        } else {
+            text
+        };
 
         Ok(CallToolResult::success(vec![Content::text(text)]))
     }
@@ -194,6 +362,60 @@ impl RustTutor {
         Ok(CallToolResult::success(vec![Content::text(text)]))
     }
 
+    #[tool(
+        name = "list_reviews",
+        description = "List past file reviews, most recent first, optionally for a single file",
+        annotations(title = "List Reviews", read_only_hint = true)
+    )]
+    async fn list_reviews(
+        &self,
+        Parameters(params): Parameters<ListReviewsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let records = {
+            let store = self.store.lock().expect("store lock poisoned");
+            store
+                .list_recent_reviews(
+                    params.file_path.as_deref(),
+                    params.limit.unwrap_or(DEFAULT_LIST_LIMIT),
+                )
+                .map_err(|e| {
+                    McpError::internal_error(format!("Failed to list reviews: {e}"), None)
+                })?
+        };
+
+        let text = join_or_empty(&records, "No reviews found", ReviewRecord::format_summary);
+
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+
+    #[tool(
+        name = "get_review",
+        description = "Get a past review by ID",
+        annotations(title = "Get Review", read_only_hint = true)
+    )]
+    async fn get_review(
+        &self,
+        Parameters(params): Parameters<GetReviewParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let record = {
+            let store = self.store.lock().expect("store lock poisoned");
+
+            store
+                .get_review_by_id(params.id)
+                .map_err(|e| McpError::internal_error(format!("Failed to get review: {e}"), None))?
+        };
+
+        let text = match record {
+            Some(r) => format!(
+                "**ID {}** `{}` ({}, {}):\n\n{}",
+                r.id, r.file_path, r.created_at, r.backend, r.review
+            ),
+            None => "No review found".to_string(),
+        };
+
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+
     #[tool(
         name = "get_file_changes",
         description = "Get a list of recent file changes",
@@ -260,65 +482,763 @@ impl RustTutor {
         &self,
         Parameters(params): Parameters<GetChangesByChangeIdParams>,
     ) -> Result<CallToolResult, McpError> {
-        let changes = {
+        let (changes, dependency_changes) = {
             let store = self.store.lock().expect("store lock poisoned");
             store
                 .get_changes_for_change_id(&params.change_id)
+                .and_then(|changes| {
+                    Ok((
+                        changes,
+                        store.get_dependency_changes_for_change_id(&params.change_id)?,
+                    ))
+                })
                 .map_err(|e| {
                     McpError::internal_error(format!("Failed to get file changes: {e}"), None)
                 })?
         };
 
+        // a Cargo.toml or Cargo.lock save is recorded as dependency changes instead of hunks
+        let text = if dependency_changes.is_empty() {
+            join_or_empty(
+                &changes,
+                "No file changes found",
+                FileChangeRecord::format_changes,
+            )
+        } else {
+            join_or_empty(
+                &dependency_changes,
+                "No file changes found",
+                DependencyChangeRecord::format_change,
+            )
+        };
+
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+
+    #[tool(
+        name = "get_file_at",
+        description = "Rebuild a watched file as it was at a point in time (RFC 3339) or right after a change ID",
+        annotations(title = "Get File At", read_only_hint = true)
+    )]
+    async fn get_file_at(
+        &self,
+        Parameters(params): Parameters<GetFileAtParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let store = self.store.lock().expect("store lock poisoned");
+
+        let (file_path, at) = match (&params.change_id, &params.at) {
+            (Some(change_id), _) => {
+                let (path, changed_at) = resolve_change(&store, change_id)?;
+                (params.file_path.clone().unwrap_or(path), changed_at)
+            }
+            (None, Some(at)) => {
+                let file_path = params.file_path.clone().ok_or_else(|| {
+                    McpError::invalid_params("`file_path` is required with `at`", None)
+                })?;
+                (file_path, parse_time(at)?)
+            }
+            (None, None) => {
+                return Err(McpError::invalid_params(
+                    "either `at` or `change_id` is required",
+                    None,
+                ))
+            }
+        };
+
+        let version = history::file_at(&store, &file_path, at).map_err(|e| {
+            McpError::internal_error(format!("Failed to rebuild {file_path}: {e}"), None)
+        })?;
+
+        let text = match version {
+            FileVersion::Present(contents) => format!(
+                "`{file_path}` as of {at}:\n\n```{}\n{contents}\n```",
+                code_fence_lang(&file_path)
+            ),
+            FileVersion::Gone {
+                at: gone_at,
+                renamed_to: Some(new_path),
+            } => format!("`{file_path}` was renamed to `{new_path}` at {gone_at}"),
+            FileVersion::Gone { at: gone_at, .. } => {
+                format!("`{file_path}` was deleted at {gone_at}")
+            }
+            FileVersion::Unknown => format!("No history for `{file_path}` as of {at}"),
+        };
+
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+
+    #[tool(
+        name = "diff_between",
+        description = "Unified diff of a watched file between two points in its history (RFC 3339 times or change IDs); `to` defaults to the file on disk",
+        annotations(title = "Diff Between", read_only_hint = true)
+    )]
+    async fn diff_between(
+        &self,
+        Parameters(params): Parameters<DiffBetweenParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let store = self.store.lock().expect("store lock poisoned");
+        let file_path = &params.file_path;
+
+        let version_at = |point: &str| -> Result<String, McpError> {
+            let at = match chrono::DateTime::parse_from_rfc3339(point) {
+                Ok(at) => at.with_timezone(&chrono::Utc),
+                Err(_) => resolve_change(&store, point)?.1,
+            };
+            match history::file_at(&store, file_path, at) {
+                Ok(FileVersion::Present(contents)) => Ok(contents),
+                Ok(_) => Ok(String::new()),
+                Err(e) => Err(McpError::internal_error(
+                    format!("Failed to rebuild {file_path} at {point}: {e}"),
+                    None,
+                )),
+            }
+        };
+
+        let old = version_at(&params.from)?;
+        let (new, to_label) = match &params.to {
+            Some(to) => (version_at(to)?, to.clone()),
+            // a missing file diffs as empty, same as a deleted one
+            None => (
+                std::fs::read_to_string(file_path).unwrap_or_default(),
+                "disk".to_string(),
+            ),
+        };
+
+        let diff = similar::TextDiff::from_lines(&old, &new)
+            .unified_diff()
+            .context_radius(params.context_lines.unwrap_or(3))
+            .header(
+                &format!("{file_path} @ {}", params.from),
+                &format!("{file_path} @ {to_label}"),
+            )
+            .to_string();
+
+        let text = if diff.is_empty() {
+            format!(
+                "No differences in `{file_path}` between {} and {to_label}",
+                params.from
+            )
+        } else {
+            format!("```diff\n{diff}```")
+        };
+
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+
+    #[tool(
+        name = "list_sessions",
+        description = "List coding sessions (runs of saves without a long idle gap), newest first, with duration, files touched and line churn",
+        annotations(title = "List Sessions", read_only_hint = true)
+    )]
+    async fn list_sessions(
+        &self,
+        Parameters(params): Parameters<ListSessionsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let limit = params
+            .limit
+            .unwrap_or(DEFAULT_LIST_LIMIT)
+            .clamp(1, MAX_LIST_LIMIT);
+        let offset = params.offset.unwrap_or(0).max(0);
+
+        let sessions = {
+            let store = self.store.lock().expect("store lock poisoned");
+            store.list_sessions(limit, offset).map_err(|e| {
+                McpError::internal_error(format!("Failed to list sessions: {e}"), None)
+            })?
+        };
+
         let text = join_or_empty(
-            &changes,
-            "No file changes found",
-            FileChangeRecord::format_changes,
+            &sessions,
+            "No sessions found",
+            SessionRecord::format_summary,
+        );
+        let text = if sessions.len() as i64 == limit {
+            format!(
+                "{text}\n\n_More may be available with `offset: {}`_",
+                offset + limit
+            )
+        } else {
+            text
+        };
+
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+
+    #[tool(
+        name = "get_session",
+        description = "Get one coding session: per-file saves and churn, dependency changes and every save in order",
+        annotations(title = "Get Session", read_only_hint = true)
+    )]
+    async fn get_session(
+        &self,
+        Parameters(params): Parameters<GetSessionParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let (session, files, dependency_changes, saves) = {
+            let store = self.store.lock().expect("store lock poisoned");
+            let Some(session) = store.get_session(params.id).map_err(|e| {
+                McpError::internal_error(format!("Failed to get session: {e}"), None)
+            })?
+            else {
+                return Ok(CallToolResult::success(vec![Content::text(format!(
+                    "No session with ID {}",
+                    params.id
+                ))]));
+            };
+
+            let details = store.get_session_files(session.id).and_then(|files| {
+                Ok((
+                    files,
+                    store.get_session_dependency_changes(session.id)?,
+                    store.get_session_saves(session.id)?,
+                ))
+            });
+            let (files, dependency_changes, saves) = details.map_err(|e| {
+                McpError::internal_error(format!("Failed to get session details: {e}"), None)
+            })?;
+            (session, files, dependency_changes, saves)
+        };
+
+        let mut text = session.format_summary();
+
+        text.push_str("\n\n| File | Saves | Added | Removed |\n|---|---:|---:|---:|\n");
+        for file in &files {
+            text.push_str(&format!(
+                "| `{}` | {} | +{} | -{} |\n",
+                file.file_path, file.saves, file.lines_added, file.lines_removed
+            ));
+        }
+
+        if !dependency_changes.is_empty() {
+            text.push_str("\n### Dependency changes\n\n");
+            for change in &dependency_changes {
+                text.push_str(&format!("- {}\n", change.format_change()));
+            }
+        }
+
+        text.push_str("\n### Saves\n\n");
+        text.push_str(&join_or_empty(
+            &saves,
+            "No saves recorded",
+            SaveEventSummary::format_summary,
+        ));
+
+        // `until` is exclusive, so step past the last save
+        text.push_str(&format!(
+            "\n\n_Review the whole session with `review_changes` since `{}` until `{}`_",
+            session.started_at.to_rfc3339(),
+            (session.ended_at + chrono::Duration::seconds(1)).to_rfc3339()
+        ));
+
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+
+    #[tool(
+        name = "recent_diagnostics",
+        description = "Show the compiler errors, warnings and clippy lints recorded by cargo check on save and run_clippy: which codes keep coming up, and the latest diagnostics",
+        annotations(title = "Recent Diagnostics", read_only_hint = true)
+    )]
+    async fn recent_diagnostics(
+        &self,
+        Parameters(params): Parameters<RecentDiagnosticsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let limit = params
+            .limit
+            .unwrap_or(DEFAULT_LIST_LIMIT)
+            .clamp(1, MAX_LIST_LIMIT);
+        let days = params
+            .days
+            .unwrap_or(DEFAULT_DIAGNOSTIC_DAYS)
+            .clamp(1, MAX_HISTORY_DAYS);
+        let since = chrono::Utc::now() - chrono::Duration::days(days);
+        let file_path = params.file_path.as_deref();
+        let level = params.level.as_deref();
+        let source = params.source.as_deref();
+
+        let (counts, recent) = {
+            let store = self.store.lock().expect("store lock poisoned");
+            store
+                .diagnostic_counts(file_path, level, source, since, MAX_LIST_LIMIT)
+                .and_then(|counts| {
+                    Ok((
+                        counts,
+                        store.recent_diagnostics(file_path, level, source, limit)?,
+                    ))
+                })
+                .map_err(|e| {
+                    McpError::internal_error(format!("Failed to get diagnostics: {e}"), None)
+                })?
+        };
+
+        if recent.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(
+                "No diagnostics recorded. Set `enabled = true` under `[cargo_check]` in config.toml to check on save",
+            )]));
+        }
+
+        let mut text = format!("## Most frequent (last {days} days)\n\n");
+        if counts.is_empty() {
+            text.push_str("Nothing in this period\n");
+        } else {
+            text.push_str(
+                "| Level | Code | Runs | Last seen | Latest message |\n|---|---|---:|---|---|\n",
+            );
+            for count in &counts {
+                text.push_str(&format!(
+                    "| {} | {} | {} | {} | {} |\n",
+                    count.level,
+                    count.code.as_deref().unwrap_or("-"),
+                    count.count,
+                    count.last_seen.format("%Y-%m-%d %H:%M"),
+                    count.message.replace('|', "\\|")
+                ));
+            }
+        }
+
+        if counts
+            .iter()
+            .any(|c| c.code.as_deref().is_some_and(|code| code.starts_with('E')))
+        {
+            text.push_str("\n_Use `explain_error` on any `E` code for the full explanation_\n");
+        }
+
+        text.push_str("\n## Recent\n\n");
+        text.push_str(&join_or_empty(
+            &recent,
+            "No diagnostics found",
+            DiagnosticRecord::format_diagnostic,
+        ));
+
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+
+    #[tool(
+        name = "run_clippy",
+        description = "Run cargo clippy on the project, or on the crate a file belongs to, and store the findings so reviews can cite real lints",
+        annotations(title = "Run Clippy")
+    )]
+    async fn run_clippy(
+        &self,
+        Parameters(params): Parameters<RunClippyParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let target = match &params.file_path {
+            Some(file_path) => std::fs::canonicalize(file_path).map_err(|e| {
+                McpError::invalid_params(format!("Can't find `{file_path}`: {e}"), None)
+            })?,
+            None => std::env::current_dir().map_err(|e| {
+                McpError::internal_error(format!("Failed to get working directory: {e}"), None)
+            })?,
+        };
+        let Some(crate_root) = cargo::crate_root(&target) else {
+            return Err(McpError::invalid_params(
+                format!("No Cargo.toml found above `{}`", target.display()),
+                None,
+            ));
+        };
+
+        let run_id = self
+            .lint_crate(&crate_root)
+            .await
+            .map_err(|e| McpError::internal_error(format!("Failed to run clippy: {e}"), None))?;
+        let findings = self
+            .store
+            .lock()
+            .expect("store lock poisoned")
+            .get_clippy_findings(run_id, None)
+            .map_err(|e| {
+                McpError::internal_error(format!("Failed to get clippy findings: {e}"), None)
+            })?;
+
+        let mut text = format!(
+            "**cargo clippy** on `{}`: {} finding{} (run {run_id})",
+            crate_root.display(),
+            findings.len(),
+            if findings.len() == 1 { "" } else { "s" }
         );
+        if findings.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(text)]));
+        }
+
+        let mut lints: Vec<(&str, usize)> = Vec::new();
+        for finding in &findings {
+            let code = finding.code.as_deref().unwrap_or("-");
+            match lints.iter_mut().find(|(c, _)| *c == code) {
+                Some((_, count)) => *count += 1,
+                None => lints.push((code, 1)),
+            }
+        }
+        lints.sort_by_key(|&(code, count)| (std::cmp::Reverse(count), code));
+        text.push_str("\n\n| Lint | Findings |\n|---|---:|\n");
+        for (code, count) in lints {
+            text.push_str(&format!("| `{code}` | {count} |\n"));
+        }
+
+        // the requested file's findings first, then the rest of the crate
+        let groups = match &params.file_path {
+            Some(_) => {
+                let file = target.to_string_lossy();
+                let (in_file, elsewhere): (Vec<_>, Vec<_>) = findings
+                    .iter()
+                    .partition(|f| f.file_path.as_deref() == Some(&*file));
+                vec![("This file", in_file), ("Rest of the crate", elsewhere)]
+            }
+            None => vec![("Findings", findings.iter().collect())],
+        };
+        let max = MAX_LIST_LIMIT as usize;
+        for (heading, group) in groups.into_iter().filter(|(_, g)| !g.is_empty()) {
+            text.push_str(&format!("\n### {heading}\n\n"));
+            for finding in group.iter().take(max) {
+                text.push_str(&finding.format_finding());
+                text.push('\n');
+            }
+            if group.len() > max {
+                text.push_str(&format!("- … and {} more\n", group.len() - max));
+            }
+        }
 
         Ok(CallToolResult::success(vec![Content::text(text)]))
     }
 
     #[tool(
         name = "check_crate_docs",
-        description = "check docs.rs for information on types",
+        description = "Look up a type, trait or function on docs.rs (or in the toolchain's own docs for std, core and alloc) and show its signature, docs, methods and trait impls. Other matching items are listed after the best match",
         annotations(title = "Check Crate Docs", read_only_hint = true,)
     )]
     async fn check_crate_docs(
         &self,
         Parameters(params): Parameters<CheckCrateDocsParams>,
     ) -> Result<CallToolResult, McpError> {
-        // check the version fallback to latest
-
-        let version = params.version.unwrap_or_else(|| String::from("latest"));
+        // check the version: what the project compiles against, else latest.
+        // Finding the project runs git and cargo, so keep it off the runtime
+        let locked = match params.version {
+            Some(_) => None,
+            None => {
+                let crate_name = params.crate_name.clone();
+                tokio::task::spawn_blocking(move || {
+                    project_root().and_then(|root| {
+                        cargo::locked_versions(&root)
+                            .version(&crate_name)
+                            .map(String::from)
+                    })
+                })
+                .await
+                .map_err(|e| {
+                    McpError::internal_error(format!("Reading Cargo.lock panicked: {e}"), None)
+                })?
+            }
+        };
+        let version = params
+            .version
+            .or_else(|| locked.clone())
+            .unwrap_or_else(|| String::from("latest"));
+        let detail = parse_detail(params.detail.as_deref())?;
+        let kind = parse_kind(params.kind.as_deref())?;
+        let limit = params
+            .limit
+            .unwrap_or(DEFAULT_DOC_MATCHES)
+            .clamp(1, MAX_LIST_LIMIT) as usize;
 
-        let results = fetch_docs(
+        let lookup = match docs_rs::fetch_docs(
             &self.client,
+            &self.store,
             &params.crate_name,
             &params.type_name,
+            kind,
             &version,
         )
         .await
-        .map_err(|e| {
-            tracing::error!(
-                "failed to get docs: reason - {} crate_name - {} type_name - {} version -{}",
-                e,
-                &params.crate_name,
-                &params.type_name,
-                version
-            );
-            McpError::internal_error(format!("failed to fetch docs: {e}"), None)
-        })?;
+        {
+            Ok(lookup) => lookup,
+            Err(e) => {
+                tracing::error!(
+                    "failed to get docs: reason - {} crate_name - {} type_name - {} version -{}",
+                    e,
+                    &params.crate_name,
+                    &params.type_name,
+                    version
+                );
+                // offline, or a private crate docs.rs never saw
+                let local = self.local_docs(
+                    &params.type_name,
+                    Some(&params.crate_name),
+                    kind,
+                    detail,
+                    limit,
+                )?;
+                return match local {
+                    Some(text) => Ok(CallToolResult::success(vec![Content::text(format!(
+                        "{text}\n_Not available from docs.rs ({e}); showing the local doc index_"
+                    ))])),
+                    None => Err(McpError::internal_error(
+                        format!("failed to fetch docs: {e}"),
+                        None,
+                    )),
+                };
+            }
+        };
+
+        let Some((best, others)) = lookup.items.split_first() else {
+            return Ok(CallToolResult::success(vec![Content::text(
+                "No Results found",
+            )]));
+        };
+
+        let mut stale = match lookup.freshness {
+            Freshness::Stale(at) => Some(at),
+            _ => None,
+        };
+
+        // a page that won't load still leaves the links worth showing
+        let mut text = match docs_rs::fetch_item(
+            &self.client,
+            &self.store,
+            &params.crate_name,
+            &version,
+            best,
+        )
+        .await
+        {
+            Ok((item, freshness)) => {
+                if let Freshness::Stale(at) = freshness {
+                    stale = Some(stale.map_or(at, |s| s.min(at)));
+                }
+                item.format_markdown(detail)
+            }
+            Err(e) => {
+                tracing::warn!("failed to get item page {}: {e}", best.url);
+                format!(
+                    "## {} `{}`\n\n{}\n\n_Couldn't load this page: {e}_\n",
+                    best.kind, best.path, best.url
+                )
+            }
+        };
+
+        if !others.is_empty() {
+            text.push_str("\n### Other matches\n\n");
+            for item in others.iter().take(limit) {
+                text.push_str(&format!("- {} `{}`: {}\n", item.kind, item.path, item.url));
+            }
+            if others.len() > limit {
+                text.push_str(&format!("- … and {} more\n", others.len() - limit));
+            }
+        }
+
+        if lookup.freshness == Freshness::Local {
+            text.push_str("\n_From the docs installed with your Rust toolchain_\n");
+        }
+
+        if locked.is_some() {
+            text.push_str(&format!(
+                "\n_Showing {} {version}, the version in Cargo.lock_\n",
+                params.crate_name
+            ));
+        }
+
+        if let Some(fetched_at) = stale {
+            text.push_str(&format!(
+                "\n_docs.rs couldn't be reached; showing docs cached at {}_",
+                fetched_at.format("%Y-%m-%d %H:%M UTC")
+            ));
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+
+    #[tool(
+        name = "build_doc_index",
+        description = "Index rustdoc output for the project's crates (from `cargo doc`, or rustdoc JSON via nightly) so docs can be searched offline, including private crates",
+        annotations(title = "Build Doc Index")
+    )]
+    async fn build_doc_index(
+        &self,
+        Parameters(params): Parameters<BuildDocIndexParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let root = tokio::task::spawn_blocking(project_root)
+            .await
+            .map_err(|e| {
+                McpError::internal_error(format!("Finding the project panicked: {e}"), None)
+            })?;
+        let Some(root) = root else {
+            return Err(McpError::invalid_params(
+                "No Cargo.toml found for the project",
+                None,
+            ));
+        };
+
+        let now = chrono::Utc::now();
+        let crate_name = params.crate_name.clone();
+        let rustdoc_json = params.rustdoc_json.unwrap_or(false);
+        let crates = tokio::task::spawn_blocking(move || -> anyhow::Result<_> {
+            let versions = cargo::locked_versions(&root);
+            if rustdoc_json {
+                let json = cargo::rustdoc_json(&root, crate_name.as_deref())?;
+                return Ok(vec![doc_index::index_rustdoc_json(&json, &versions, now)?]);
+            }
+            let doc_dir = cargo::target_dir(&root).join("doc");
+            if !doc_dir.is_dir() {
+                anyhow::bail!(
+                    "no rustdoc output in `{}`. Run `cargo doc` first, or pass `rustdoc_json = true`",
+                    doc_dir.display()
+                );
+            }
+            doc_index::index_doc_dir(&doc_dir, &versions, crate_name.as_deref(), now)
+        })
+        .await
+        .map_err(|e| McpError::internal_error(format!("Doc indexing panicked: {e}"), None))?
+        .map_err(|e| McpError::internal_error(format!("Failed to build doc index: {e}"), None))?;
+
+        if crates.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "No docs found for `{}`. Run `cargo doc` first, or pass `rustdoc_json = true`",
+                params.crate_name.as_deref().unwrap_or("the project")
+            ))]));
+        }
+
+        let indexed = {
+            let store = self.store.lock().expect("store lock poisoned");
+            for (crate_name, records) in &crates {
+                store.replace_doc_items(crate_name, records).map_err(|e| {
+                    McpError::internal_error(format!("Failed to save doc index: {e}"), None)
+                })?;
+            }
+            store.list_doc_crates().map_err(|e| {
+                McpError::internal_error(format!("Failed to list doc index: {e}"), None)
+            })?
+        };
+
+        let items: usize = crates.iter().map(|(_, records)| records.len()).sum();
+        let text = format!(
+            "Indexed {items} items from {} crate{}\n\n| Crate | Version | Source | Items | Indexed |\n|---|---|---|---:|---|\n{}",
+            crates.len(),
+            if crates.len() == 1 { "" } else { "s" },
+            indexed
+                .iter()
+                .map(|c| c.format_row())
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+
+    #[tool(
+        name = "search_local_docs",
+        description = "Search the local doc index built by build_doc_index by item path and kind; works offline and for private crates",
+        annotations(title = "Search Local Docs", read_only_hint = true)
+    )]
+    async fn search_local_docs(
+        &self,
+        Parameters(params): Parameters<SearchLocalDocsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let detail = parse_detail(params.detail.as_deref())?;
+        let kind = parse_kind(params.kind.as_deref())?;
+        let limit = params
+            .limit
+            .unwrap_or(DEFAULT_DOC_MATCHES)
+            .clamp(1, MAX_LIST_LIMIT);
+
+        let found = self.local_docs(
+            &params.query,
+            params.crate_name.as_deref(),
+            kind,
+            detail,
+            limit as usize,
+        )?;
+        let text = match found {
+            Some(text) => text,
+            None => {
+                let empty = self
+                    .store
+                    .lock()
+                    .expect("store lock poisoned")
+                    .list_doc_crates()
+                    .map_err(|e| {
+                        McpError::internal_error(format!("Failed to list doc index: {e}"), None)
+                    })?
+                    .is_empty();
+                if empty {
+                    "The local doc index is empty. Run `build_doc_index` first".to_string()
+                } else {
+                    "No Results found".to_string()
+                }
+            }
+        };
+
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+
+    #[tool(
+        name = "list_dependencies",
+        description = "List the project's direct dependencies with the version requested in Cargo.toml, the version locked in Cargo.lock, and the features asked for",
+        annotations(title = "List Dependencies", read_only_hint = true)
+    )]
+    async fn list_dependencies(
+        &self,
+        Parameters(params): Parameters<ListDependenciesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let found = tokio::task::spawn_blocking(|| {
+            let root = project_root()?;
+            Some((
+                cargo::workspace_manifests(&root),
+                cargo::locked_versions(&root),
+            ))
+        })
+        .await
+        .map_err(|e| McpError::internal_error(format!("Reading manifests panicked: {e}"), None))?;
+        let Some((members, locked)) = found else {
+            return Err(McpError::invalid_params(
+                "No Cargo.toml found for the project",
+                None,
+            ));
+        };
+
+        let mut rows = Vec::new();
+        for (package, manifest_path) in &members {
+            let deps = std::fs::read_to_string(manifest_path)
+                .map_err(anyhow::Error::from)
+                .and_then(|text| manifest::parse_manifest(&text))
+                .map_err(|e| {
+                    McpError::internal_error(
+                        format!("Failed to read {}: {e}", manifest_path.display()),
+                        None,
+                    )
+                })?;
 
-        let text = if results.is_empty() {
-            "No Results found".to_string()
+            for ((section, crate_name), dep) in deps {
+                if params.section.as_deref().is_some_and(|s| s != section) {
+                    continue;
+                }
+                let mut features = dep.features.clone();
+                if !dep.default_features {
+                    features.insert(0, "no default features".to_string());
+                }
+                rows.push(format!(
+                    "| {}`{crate_name}` | {section} | {} | {} | {} |",
+                    // workspaces list each member's dependencies separately
+                    if members.len() > 1 {
+                        format!("{package}: ")
+                    } else {
+                        String::new()
+                    },
+                    dep.version.as_deref().unwrap_or("-"),
+                    locked.version(&crate_name).unwrap_or("-"),
+                    if features.is_empty() {
+                        "-".to_string()
+                    } else {
+                        features.join(", ")
+                    }
+                ));
+            }
+        }
+
+        let text = if rows.is_empty() {
+            match &params.section {
+                Some(section) => format!("No `{section}` found"),
+                None => "No dependencies found".to_string(),
+            }
         } else {
             format!(
-                "**Results**:\n\n{}",
-                results
-                    .iter()
-                    .map(|r| format!("{:?}\n\n", r))
-                    .collect::<Vec<_>>()
-                    .join("\n---\n")
+                "| Crate | Section | Requested | Locked | Features |\n|---|---|---|---|---|\n{}",
+                rows.join("\n")
             )
         };
 
@@ -340,6 +1260,452 @@ impl RustTutor {
 
         Ok(CallToolResult::success(vec![Content::text(man_page)]))
     }
+
+    #[tool(
+        name = "explain_error",
+        description = "Explain a rustc error code (e.g. E0502) using `rustc --explain`, with how often the student has hit it recently",
+        annotations(title = "Explain Error", read_only_hint = true)
+    )]
+    async fn explain_error(
+        &self,
+        Parameters(params): Parameters<ExplainErrorParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let Some(code) = rustc::normalize_code(&params.code) else {
+            return Err(McpError::invalid_params(
+                format!("`{}` is not a rustc error code like E0502", params.code),
+                None,
+            ));
+        };
+
+        let cached = self
+            .store
+            .lock()
+            .expect("store lock poisoned")
+            .get_error_explanation(&code)
+            .map_err(|e| {
+                McpError::internal_error(format!("Failed to get explanation: {e}"), None)
+            })?;
+
+        // explanations change between releases, so a cached one is only
+        // reused for the toolchain that wrote it (or when rustc is gone)
+        let installed = tokio::task::spawn_blocking(rustc::version)
+            .await
+            .map_err(|e| McpError::internal_error(format!("explain task failed: {e}"), None))?;
+        let explanation = match cached {
+            Some(explanation) if installed.is_none() || explanation.rustc_version == installed => {
+                explanation
+            }
+            cached => {
+                let lookup = code.clone();
+                let fetched = tokio::task::spawn_blocking(move || rustc::explain_error(&lookup))
+                    .await
+                    .map_err(|e| {
+                        McpError::internal_error(format!("explain task failed: {e}"), None)
+                    })?;
+                match (fetched, cached) {
+                    (Ok(text), _) => {
+                        let explanation = ErrorExplanation {
+                            code: code.clone(),
+                            explanation: text,
+                            rustc_version: installed,
+                            fetched_at: chrono::Utc::now(),
+                        };
+                        // the cache is a convenience, so a failed insert still answers
+                        if let Err(e) = self
+                            .store
+                            .lock()
+                            .expect("store lock poisoned")
+                            .save_error_explanation(&explanation)
+                        {
+                            tracing::warn!("failed to cache explanation for {code}: {e}");
+                        }
+                        explanation
+                    }
+                    (Err(e), Some(cached)) => {
+                        tracing::warn!("failed to re-explain {code}, using the cached copy: {e}");
+                        cached
+                    }
+                    (Err(e), None) => {
+                        return Err(McpError::internal_error(
+                            format!("Failed to explain {code}: {e}"),
+                            None,
+                        ));
+                    }
+                }
+            }
+        };
+
+        let since = chrono::Utc::now() - chrono::Duration::days(DEFAULT_DIAGNOSTIC_DAYS);
+        let ((recent, total), occurrences) = {
+            let store = self.store.lock().expect("store lock poisoned");
+            store
+                .count_code_occurrences(&code, since)
+                .and_then(|counts| Ok((counts, store.get_diagnostics_for_code(&code, 3)?)))
+                .map_err(|e| {
+                    McpError::internal_error(format!("Failed to get diagnostics: {e}"), None)
+                })?
+        };
+
+        let mut text = format!("## {code}\n\n");
+        if total > 0 {
+            text.push_str(&format!(
+                "You've hit {code} {recent} time{} in the last {DEFAULT_DIAGNOSTIC_DAYS} days ({total} in all). Most recently:\n\n{}\n\n---\n\n",
+                if recent == 1 { "" } else { "s" },
+                occurrences
+                    .iter()
+                    .map(DiagnosticRecord::format_finding)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ));
+        }
+        text.push_str(explanation.explanation.trim_end());
+        text.push_str(&format!(
+            "\n\n_From `rustc --explain`{}, fetched {}_",
+            explanation
+                .rustc_version
+                .map(|v| format!(" ({v})"))
+                .unwrap_or_default(),
+            explanation.fetched_at.format("%Y-%m-%d")
+        ));
+
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+
+    #[tool(
+        name = "learning_report",
+        description = "Summarize which rustc errors and clippy lints the student keeps hitting, which they've stopped hitting, and what to study next",
+        annotations(title = "Learning Report", read_only_hint = true)
+    )]
+    async fn learning_report(
+        &self,
+        Parameters(params): Parameters<LearningReportParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let weeks = params
+            .weeks
+            .unwrap_or(DEFAULT_REPORT_WEEKS)
+            .clamp(1, MAX_REPORT_WEEKS);
+        let now = chrono::Utc::now();
+        let since = now - chrono::Duration::weeks(weeks);
+
+        let (summaries, weekly_checks, weekly_codes) = {
+            let store = self.store.lock().expect("store lock poisoned");
+            store
+                .get_code_summaries()
+                .and_then(|summaries| {
+                    Ok((
+                        summaries,
+                        store.get_weekly_checks(since)?,
+                        store.get_weekly_codes(since)?,
+                    ))
+                })
+                .map_err(|e| {
+                    McpError::internal_error(format!("Failed to build learning report: {e}"), None)
+                })?
+        };
+
+        let text = learning::build_report(&summaries, &weekly_checks, &weekly_codes, weeks, now);
+
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+
+    #[tool(
+        name = "usage_report",
+        description = "Summarize LLM API token usage and estimated spend by day, tool and file",
+        annotations(title = "Usage Report", read_only_hint = true)
+    )]
+    async fn usage_report(
+        &self,
+        Parameters(params): Parameters<UsageReportParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let days = params
+            .days
+            .unwrap_or(DEFAULT_USAGE_DAYS)
+            .clamp(1, MAX_HISTORY_DAYS);
+        let since = chrono::Utc::now() - chrono::Duration::days(days);
+
+        let sections = {
+            let store = self.store.lock().expect("store lock poisoned");
+            [UsageGrouping::Day, UsageGrouping::Tool, UsageGrouping::File]
+                .into_iter()
+                .map(|grouping| Ok((grouping, store.usage_summary(grouping, since)?)))
+                .collect::<anyhow::Result<Vec<_>>>()
+                .map_err(|e| {
+                    McpError::internal_error(format!("Failed to summarize usage: {e}"), None)
+                })?
+        };
+
+        let text = usage::render_report(days, &sections, &self.pricing);
+
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+
+    #[tool(
+        name = "store_info",
+        description = "Show the tutor database location, schema version and row counts per table",
+        annotations(title = "Store Info", read_only_hint = true)
+    )]
+    async fn store_info(&self) -> Result<CallToolResult, McpError> {
+        let info = self
+            .store
+            .lock()
+            .expect("store lock poisoned")
+            .store_info()
+            .map_err(|e| {
+                McpError::internal_error(format!("Failed to read store info: {e}"), None)
+            })?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            info.format_summary(),
+        )]))
+    }
+
+    // run_review - this streams a review from the configured backend (or the
+    // host via sampling), records usage and saves the result once for each
+    // reviewed (file, content hash). Returns None when there is nothing to run
+    // it on, so the caller can fall back to returning the prompt for the host
+    // LLM to follow
+    async fn run_review(
+        &self,
+        ctx: &RequestContext<RoleServer>,
+        tool: &str,
+        task: Task,
+        prompt: &str,
+        files: &[(String, String)],
+    ) -> Result<Option<String>, McpError> {
+        let Some(backend) = self.backend_for(ctx) else {
+            return Ok(None);
+        };
+
+        let started = Instant::now();
+        let review = match stream_completion(backend.as_ref(), task, prompt, ctx).await {
+            Ok(review) => review,
+            // a client that cancelled wants the review stopped, not the prompt
+            Err(e) if ctx.ct.is_cancelled() => return Err(e),
+            // sampling is best effort — let the caller fall back to prompt stuffing
+            Err(e) if self.backend.is_none() => {
+                tracing::warn!("sampling failed, returning review prompt instead: {e:?}");
+                return Ok(None);
+            }
+            Err(e) => return Err(e),
+        };
+        let paths: Vec<&str> = files.iter().map(|(path, _)| path.as_str()).collect();
+        self.record_api_call(
+            tool,
+            Some(&paths.join(", ")),
+            backend.name(),
+            &review,
+            started,
+        );
+
+        let mut text = review.text;
+
+        let store = self.store.lock().expect("store lock poisoned");
+        let ids = files
+            .iter()
+            .map(|(file_path, content_hash)| {
+                store.save_review(file_path, content_hash, &text, backend.name())
+            })
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map_err(|e| McpError::internal_error(format!("Failed to save review: {e}"), None))?;
+        drop(store);
+
+        let ids: Vec<String> = ids.iter().map(ToString::to_string).collect();
+        match ids.as_slice() {
+            [id] => text.push_str(&format!("\n\n**Review ID**: {id}")),
+            ids => text.push_str(&format!("\n\n**Review IDs**: {}", ids.join(", "))),
+        }
+
+        Ok(Some(text))
+    }
+
+    // local_docs - this answers a lookup from the local doc index: the best
+    // match rendered, then up to `limit` other matches. None when nothing matches
+    fn local_docs(
+        &self,
+        query: &str,
+        crate_name: Option<&str>,
+        kind: Option<&str>,
+        detail: Detail,
+        limit: usize,
+    ) -> Result<Option<String>, McpError> {
+        // rustdoc names crates with underscores
+        let crate_name = crate_name.map(|name| name.replace('-', "_"));
+        let records = self
+            .store
+            .lock()
+            .expect("store lock poisoned")
+            .get_doc_items(crate_name.as_deref(), kind)
+            .map_err(|e| {
+                McpError::internal_error(format!("Failed to search doc index: {e}"), None)
+            })?;
+        let mut ranked: Vec<_> = records
+            .into_iter()
+            .filter_map(|r| Some((docs_rs::rank(&r.path, &r.kind, query)?, r)))
+            .collect();
+        // the same path can be indexed for two crates
+        ranked.sort_by(|a, b| (&a.0, &a.1.crate_name).cmp(&(&b.0, &b.1.crate_name)));
+        let records: Vec<_> = ranked.into_iter().map(|(_, r)| r).collect();
+
+        let Some((best, others)) = records.split_first() else {
+            return Ok(None);
+        };
+
+        let mut text = doc_index::render(best, detail).unwrap_or_else(|e| {
+            format!(
+                "## {} `{}`\n\n_Couldn't read {}: {e}. Run `build_doc_index` again_\n",
+                best.kind, best.path, best.location
+            )
+        });
+        if !others.is_empty() {
+            text.push_str("\n### Other matches\n\n");
+            for item in others.iter().take(limit) {
+                text.push_str(&format!(
+                    "- {} `{}::{}`\n",
+                    item.kind, item.crate_name, item.path
+                ));
+            }
+            if others.len() > limit {
+                text.push_str(&format!("- … and {} more\n", others.len() - limit));
+            }
+        }
+
+        Ok(Some(text))
+    }
+
+    // lint_crate - this runs clippy over the crate at `crate_root` off the
+    // async runtime and stores the run, returning its id
+    async fn lint_crate(&self, crate_root: &Path) -> anyhow::Result<i64> {
+        let root = crate_root.to_path_buf();
+        let diagnostics = tokio::task::spawn_blocking(move || cargo::clippy(&root, &[])).await??;
+
+        let ran_at = chrono::Utc::now();
+        let findings: Vec<DiagnosticRecord> = diagnostics
+            .into_iter()
+            .map(|d| d.into_record("clippy", None, None, ran_at))
+            .collect();
+
+        self.store
+            .lock()
+            .expect("store lock poisoned")
+            .save_clippy_run(&crate_root.to_string_lossy(), ran_at, &findings)
+    }
+
+    // clippy_for_review - this gets clippy's findings for one file, reusing
+    // the crate's last run when the file hasn't changed since, otherwise
+    // running clippy now. None when the file isn't in a cargo project or
+    // clippy can't run; reviews go ahead without lint output then. A fresh run
+    // on a cold crate can take minutes, so the client is told and can cancel it
+    async fn clippy_for_review(
+        &self,
+        file_path: &str,
+        ctx: &RequestContext<RoleServer>,
+    ) -> Result<Option<Vec<DiagnosticRecord>>, McpError> {
+        let Ok(path) = std::fs::canonicalize(file_path) else {
+            return Ok(None);
+        };
+        let Some(crate_root) = cargo::crate_root(&path) else {
+            return Ok(None);
+        };
+        let Some(modified) = std::fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .ok()
+            .map(chrono::DateTime::<chrono::Utc>::from)
+        else {
+            return Ok(None);
+        };
+
+        let latest = self
+            .store
+            .lock()
+            .expect("store lock poisoned")
+            .latest_clippy_run(&crate_root.to_string_lossy())
+            .unwrap_or_else(|e| {
+                tracing::warn!("failed to look up clippy runs: {e}");
+                None
+            });
+        let run_id = match latest {
+            Some((run_id, ran_at)) if ran_at >= modified => run_id,
+            _ => {
+                if let Some(progress_token) = ctx.meta.get_progress_token() {
+                    let notification = ProgressNotificationParam {
+                        progress_token,
+                        progress: 0.0,
+                        total: None,
+                        message: Some(format!(
+                            "Running clippy on {} before reviewing\n",
+                            crate_root.display()
+                        )),
+                    };
+                    if let Err(e) = ctx.peer.notify_progress(notification).await {
+                        tracing::debug!("failed to send progress notification: {e}");
+                    }
+                }
+
+                let result = tokio::select! {
+                    result = self.lint_crate(&crate_root) => result,
+                    _ = ctx.ct.cancelled() => {
+                        tracing::info!("review cancelled by client");
+                        return Err(McpError::internal_error("review cancelled by client", None));
+                    }
+                };
+                match result {
+                    Ok(run_id) => run_id,
+                    Err(e) => {
+                        tracing::warn!("clippy failed, reviewing without it: {e}");
+                        return Ok(None);
+                    }
+                }
+            }
+        };
+
+        Ok(self
+            .store
+            .lock()
+            .expect("store lock poisoned")
+            .get_clippy_findings(run_id, Some(&path.to_string_lossy()))
+            .inspect_err(|e| tracing::warn!("failed to get clippy findings: {e}"))
+            .ok())
+    }
+
+    // backend_for - the configured API backend wins; without one, use the
+    // client's own LLM through sampling if it supports it
+    fn backend_for(&self, ctx: &RequestContext<RoleServer>) -> Option<Arc<dyn ReviewBackend>> {
+        self.backend.clone().or_else(|| {
+            SamplingBackend::for_peer(&ctx.peer, self.max_tokens)
+                .map(|b| Arc::new(b) as Arc<dyn ReviewBackend>)
+        })
+    }
+
+    // record_api_call - usage tracking is best effort, so a failed insert is
+    // logged rather than failing the tool call that already succeeded
+    fn record_api_call(
+        &self,
+        tool: &str,
+        file_path: Option<&str>,
+        backend: &str,
+        completion: &Completion,
+        started: Instant,
+    ) {
+        let record = ApiCallRecord {
+            tool: tool.to_string(),
+            backend: backend.to_string(),
+            model: completion.model.clone(),
+            file_path: file_path.map(ToString::to_string),
+            input_tokens: completion.usage.input_tokens,
+            output_tokens: completion.usage.output_tokens,
+            latency_ms: started.elapsed().as_millis() as i64,
+            created_at: chrono::Utc::now(),
+        };
+
+        if let Err(e) = self
+            .store
+            .lock()
+            .expect("store lock poisoned")
+            .save_api_call(&record)
+        {
+            tracing::warn!("failed to record api call: {e}");
+        }
+    }
 }
 
 #[tool_handler]
@@ -353,6 +1719,87 @@ impl ServerHandler for RustTutor {
     }
 }
 
+// resolve_change - this maps a change ID to the file it touched and when
+fn resolve_change(
+    store: &TutorStore,
+    change_id: &str,
+) -> Result<(String, chrono::DateTime<chrono::Utc>), McpError> {
+    store
+        .get_change_time(change_id)
+        .map_err(|e| McpError::internal_error(format!("Failed to look up change: {e}"), None))?
+        .ok_or_else(|| McpError::invalid_params(format!("no change with ID {change_id}"), None))
+}
+
+fn code_fence_lang(file_path: &str) -> &'static str {
+    match std::path::Path::new(file_path)
+        .extension()
+        .and_then(|e| e.to_str())
+    {
+        Some("rs") => "rust",
+        Some("toml" | "lock") => "toml",
+        Some("sql") => "sql",
+        _ => "",
+    }
+}
+
+// project_root - this finds the cargo project the tutor serves: the one at
+// the watcher's git root, else the one around the working directory
+fn project_root() -> Option<std::path::PathBuf> {
+    watcher::detect_project_root()
+        .filter(|root| root.join("Cargo.toml").is_file())
+        .or_else(|| cargo::crate_root(&std::env::current_dir().ok()?))
+}
+
+fn parse_detail(detail: Option<&str>) -> Result<Detail, McpError> {
+    match detail {
+        None | Some("summary") => Ok(Detail::Summary),
+        Some("full") => Ok(Detail::Full),
+        Some(other) => Err(McpError::invalid_params(
+            format!("`detail` must be `summary` or `full`, not `{other}`"),
+            None,
+        )),
+    }
+}
+
+fn parse_kind(kind: Option<&str>) -> Result<Option<&'static str>, McpError> {
+    kind.map(|kind| {
+        docs_rs::normalize_kind(kind).ok_or_else(|| {
+            McpError::invalid_params(
+                format!(
+                    "`{kind}` isn't an item kind; use one of {}",
+                    docs_rs::KINDS.join(", ")
+                ),
+                None,
+            )
+        })
+    })
+    .transpose()
+}
+
+fn parse_time(value: &str) -> Result<chrono::DateTime<chrono::Utc>, McpError> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&chrono::Utc))
+        .map_err(|e| {
+            McpError::invalid_params(format!("`{value}` is not an RFC 3339 time: {e}"), None)
+        })
+}
+
+// clippy_section - this appends clippy's findings to a review prompt so lint
+// names come from real output rather than the model's memory
+fn clippy_section(findings: &[DiagnosticRecord]) -> String {
+    if findings.is_empty() {
+        return "\n\n---\n\n`cargo clippy` reported nothing for this file.".to_string();
+    }
+    format!(
+        "\n\n---\n\n**`cargo clippy` findings for this file:**\n\n{}",
+        findings
+            .iter()
+            .map(DiagnosticRecord::format_finding)
+            .collect::<Vec<_>>()
+            .join("\n")
+    )
+}
+
 fn join_or_empty<T>(items: &[T], msg: &str, f: impl Fn(&T) -> String) -> String {
     if items.is_empty() {
         msg.to_string()
@@ -360,3 +1807,70 @@ fn join_or_empty<T>(items: &[T], msg: &str, f: impl Fn(&T) -> String) -> String
         items.iter().map(f).collect::<Vec<_>>().join("\n\n---\n\n")
     }
 }
+
+// stream_completion - this runs a streaming completion, forwarding generated text to
+// the client as progress notifications (when it asked for them with a
+// progress token) and dropping the upstream request if the client cancels
+async fn stream_completion(
+    backend: &dyn ReviewBackend,
+    task: Task,
+    prompt: &str,
+    ctx: &RequestContext<RoleServer>,
+) -> Result<Completion, McpError> {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+
+    let review = async move {
+        let on_delta = move |delta: &str| {
+            let _ = tx.send(delta.to_string());
+        };
+        backend.complete_streaming(task, prompt, &on_delta).await
+    };
+
+    // each notification carries the text generated since the previous one;
+    // `progress` is the number of bytes generated so far
+    let forward = async {
+        let Some(progress_token) = ctx.meta.get_progress_token() else {
+            while rx.recv().await.is_some() {}
+            return;
+        };
+
+        let mut generated = 0;
+        let mut pending = String::new();
+        let mut last_sent = Instant::now();
+        loop {
+            let delta = rx.recv().await;
+            let done = delta.is_none();
+            if let Some(delta) = delta {
+                generated += delta.len();
+                pending.push_str(&delta);
+            }
+
+            if !pending.is_empty() && (done || last_sent.elapsed() >= PROGRESS_INTERVAL) {
+                let notification = ProgressNotificationParam {
+                    progress_token: progress_token.clone(),
+                    progress: generated as f64,
+                    total: None,
+                    message: Some(std::mem::take(&mut pending)),
+                };
+                if let Err(e) = ctx.peer.notify_progress(notification).await {
+                    tracing::debug!("failed to send progress notification: {e}");
+                }
+                last_sent = Instant::now();
+            }
+
+            if done {
+                break;
+            }
+        }
+    };
+
+    tokio::select! {
+        (result, ()) = async { tokio::join!(review, forward) } => {
+            result.map_err(|e| e.into_mcp_error(backend.name()))
+        }
+        _ = ctx.ct.cancelled() => {
+            tracing::info!("review cancelled by client");
+            Err(McpError::internal_error("review cancelled by client", None))
+        }
+    }
+}
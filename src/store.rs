@@ -2,12 +2,42 @@ use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use rusqlite::params;
 use std::{
+    collections::HashMap,
     fs,
-    path::Path,
+    path::{Path, PathBuf},
     process::Command,
     sync::{Arc, Mutex},
 };
 
+use crate::migrations;
+
+// sessions with the number of distinct files their saves touched
+const SESSION_SELECT: &str = r##"
+    SELECT s.id, s.started_at, s.ended_at, s.save_count, s.lines_added, s.lines_removed,
+           (
+               SELECT COUNT(*) FROM (
+                   SELECT file_path FROM file_changes WHERE session_id = s.id
+                   UNION
+                   SELECT file_path FROM dependency_changes WHERE session_id = s.id
+               )
+           )
+    FROM sessions s
+"##;
+
+// how long an expired docs.rs response is kept as an offline fallback before
+// it's pruned
+const HTTP_CACHE_GRACE_DAYS: i64 = 30;
+
+// rustc error codes and clippy lints, one row per diagnostic, tagged with the
+// save or clippy run that reported them and the week (starting Monday) it was in
+const STRUGGLE_HITS: &str = r##"
+    SELECT code, message, created_at,
+           COALESCE(change_id, 'run ' || run_id) AS origin,
+           date(created_at, 'weekday 0', '-6 days') AS week
+    FROM diagnostics
+    WHERE (level = 'error' AND code LIKE 'E%') OR code LIKE 'clippy::%'
+"##;
+
 trait FromRow: Sized {
     fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self>;
 }
@@ -40,6 +70,38 @@ impl FromRow for ScaffoldRecord {
     }
 }
 
+/// A full-text search hit, with matched terms wrapped in `**`.
+#[derive(Debug, Clone)]
+pub struct ScaffoldMatch {
+    pub id: i64,
+    pub description: String, // highlighted description
+    pub snippet: String,     // best matching excerpt of the content
+    pub created_at: DateTime<Utc>,
+}
+
+impl ScaffoldMatch {
+    pub fn format_match(&self) -> String {
+        format!(
+            "**ID {}** ({}): {}\n> {}",
+            self.id,
+            self.created_at.format("%Y-%m-%d"),
+            self.description,
+            self.snippet.replace('\n', " ")
+        )
+    }
+}
+
+impl FromRow for ScaffoldMatch {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            description: row.get(1)?,
+            snippet: row.get(2)?,
+            created_at: row.get(3)?,
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct FileChangeRecord {
     pub id: i64,
@@ -53,6 +115,13 @@ pub struct FileChangeRecord {
     pub before_lines: String,
     pub after_lines: String,
     pub changed_at: DateTime<Utc>,
+    pub kind: String,                 // create, modify, delete or rename
+    pub old_path: Option<String>,     // where a renamed file used to live
+    pub new_text: Option<String>,     // exact text of new_start..new_start+new_count
+    pub content_hash: Option<String>, // sha256 of the whole file after this save
+    pub lines_added: i64,
+    pub lines_removed: i64,
+    pub session_id: Option<i64>, // coding session the save belongs to
 }
 
 impl FromRow for FileChangeRecord {
@@ -69,6 +138,13 @@ impl FromRow for FileChangeRecord {
             before_lines: row.get(8)?,
             after_lines: row.get(9)?,
             changed_at: row.get(10)?,
+            kind: row.get(11)?,
+            old_path: row.get(12)?,
+            new_text: row.get(13)?,
+            content_hash: row.get(14)?,
+            lines_added: row.get(15)?,
+            lines_removed: row.get(16)?,
+            session_id: row.get(17)?,
         })
     }
 }
@@ -76,10 +152,11 @@ impl FromRow for FileChangeRecord {
 impl FileChangeRecord {
     pub fn format_changes(&self) -> String {
         format!(
-            "**ID {}** `{}` ({}):\n\n@@ -{},{} +{},{}
+            "**ID {}** `{}`{} ({}):\n\n@@ -{},{} +{},{}
             @@\n\nBefore:\n```\n{}\n```\n\nAfter:\n```\n{}\n```",
             self.id,
             self.file_path,
+            describe_kind(&self.kind, self.old_path.as_deref()),
             self.changed_at,
             self.old_start,
             self.old_count,
@@ -91,12 +168,50 @@ impl FileChangeRecord {
     }
 }
 
+// describe_kind - this is empty for plain edits, which are the common case
+pub fn describe_kind(kind: &str, old_path: Option<&str>) -> String {
+    match (kind, old_path) {
+        ("rename", Some(old_path)) => format!(" — renamed from `{old_path}`"),
+        ("create", _) => " — created".to_string(),
+        ("delete", _) => " — deleted".to_string(),
+        _ => String::new(),
+    }
+}
+
+/// A full copy of a file, taken when it is first seen, created, renamed and
+/// every few saves, so reconstruction never has to replay much history.
+#[derive(Debug, Clone)]
+pub struct SnapshotRecord {
+    pub file_path: String,
+    pub change_id: Option<String>, // the save this snapshot is the result of
+    pub reason: String,            // base, create, rename, dependencies or periodic
+    pub content: String,
+    pub content_hash: String,
+    pub created_at: DateTime<Utc>, // same as the save's changed_at
+}
+
+impl FromRow for SnapshotRecord {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            file_path: row.get(0)?,
+            change_id: row.get(1)?,
+            reason: row.get(2)?,
+            content: row.get(3)?,
+            content_hash: row.get(4)?,
+            created_at: row.get(5)?,
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SaveEventSummary {
     pub change_id: String,
     pub file_path: String,
     pub changed_at: DateTime<Utc>,
     pub hunk_count: i64,
+    pub dependency_count: i64, // set instead of hunk_count for Cargo.toml/Cargo.lock saves
+    pub kind: String,
+    pub old_path: Option<String>,
 }
 
 impl FromRow for SaveEventSummary {
@@ -106,26 +221,541 @@ impl FromRow for SaveEventSummary {
             file_path: row.get(1)?,
             changed_at: row.get(2)?,
             hunk_count: row.get(3)?,
+            dependency_count: row.get(4)?,
+            kind: row.get(5)?,
+            old_path: row.get(6)?,
         })
     }
 }
 
 impl SaveEventSummary {
     pub fn format_summary(&self) -> String {
+        let (count, noun) = if self.dependency_count > 0 {
+            (self.dependency_count, "dependency change")
+        } else {
+            (self.hunk_count, "hunk")
+        };
+        let kind = describe_kind(&self.kind, self.old_path.as_deref());
         format!(
-            "**ID {}** `{}` ({}):\n\n{} hunk{}",
+            "**ID {}** `{}`{kind} ({}):\n\n{} {noun}{}",
             self.change_id,
             self.file_path,
             self.changed_at,
-            self.hunk_count,
-            if self.hunk_count == 1 { "" } else { "s" }
+            count,
+            if count == 1 { "" } else { "s" }
+        )
+    }
+}
+
+/// A dependency added, removed or changed by one save of a Cargo.toml or Cargo.lock.
+#[derive(Debug, Clone)]
+pub struct DependencyChangeRecord {
+    pub change_id: String,
+    pub file_path: String,
+    pub crate_name: String,
+    pub section: String, // dependencies, dev-dependencies, Cargo.lock, ...
+    pub kind: String,    // added, removed, bumped or changed
+    pub old_version: Option<String>,
+    pub new_version: Option<String>,
+    pub changed_at: DateTime<Utc>,
+    pub session_id: Option<i64>,
+}
+
+impl FromRow for DependencyChangeRecord {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            change_id: row.get(0)?,
+            file_path: row.get(1)?,
+            crate_name: row.get(2)?,
+            section: row.get(3)?,
+            kind: row.get(4)?,
+            old_version: row.get(5)?,
+            new_version: row.get(6)?,
+            changed_at: row.get(7)?,
+            session_id: row.get(8)?,
+        })
+    }
+}
+
+impl DependencyChangeRecord {
+    pub fn format_change(&self) -> String {
+        let version = |v: &Option<String>| v.clone().unwrap_or_else(|| "*".to_string());
+        let detail = match self.kind.as_str() {
+            "added" => format!("added {}", version(&self.new_version)),
+            "removed" => format!("removed (was {})", version(&self.old_version)),
+            "bumped" => format!(
+                "{} → {}",
+                version(&self.old_version),
+                version(&self.new_version)
+            ),
+            _ => format!("changed ({})", version(&self.new_version)),
+        };
+        format!(
+            "**{}** [{}] `{}` ({}): {detail}",
+            self.crate_name, self.section, self.file_path, self.changed_at
+        )
+    }
+}
+
+/// A run of saves with no idle gap longer than the watcher's `session_gap_mins`.
+#[derive(Debug, Clone)]
+pub struct SessionRecord {
+    pub id: i64,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>, // time of the last save
+    pub save_count: i64,
+    pub lines_added: i64,
+    pub lines_removed: i64,
+    pub file_count: i64,
+}
+
+impl FromRow for SessionRecord {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            started_at: row.get(1)?,
+            ended_at: row.get(2)?,
+            save_count: row.get(3)?,
+            lines_added: row.get(4)?,
+            lines_removed: row.get(5)?,
+            file_count: row.get(6)?,
+        })
+    }
+}
+
+impl SessionRecord {
+    pub fn format_summary(&self) -> String {
+        let minutes = (self.ended_at - self.started_at).num_minutes();
+        format!(
+            "**Session {}** {} → {} ({minutes} min): {} save{} across {} file{}, +{}/-{} (net {:+})",
+            self.id,
+            self.started_at.format("%Y-%m-%d %H:%M"),
+            self.ended_at.format("%H:%M"),
+            self.save_count,
+            if self.save_count == 1 { "" } else { "s" },
+            self.file_count,
+            if self.file_count == 1 { "" } else { "s" },
+            self.lines_added,
+            self.lines_removed,
+            self.lines_added - self.lines_removed
+        )
+    }
+}
+
+/// Per-file activity within one session.
+#[derive(Debug, Clone)]
+pub struct SessionFileRow {
+    pub file_path: String,
+    pub saves: i64,
+    pub lines_added: i64,
+    pub lines_removed: i64,
+}
+
+impl FromRow for SessionFileRow {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            file_path: row.get(0)?,
+            saves: row.get(1)?,
+            lines_added: row.get(2)?,
+            lines_removed: row.get(3)?,
+        })
+    }
+}
+
+/// A compiler diagnostic from a `cargo check` run after a save, or a finding
+/// from a `cargo clippy` run.
+#[derive(Debug, Clone)]
+pub struct DiagnosticRecord {
+    pub change_id: Option<String>, // the save that triggered the check; None for clippy
+    pub session_id: Option<i64>,
+    pub source: String,      // check or clippy
+    pub run_id: Option<i64>, // the clippy run it came from
+    pub level: String,
+    pub code: Option<String>,
+    pub message: String,
+    pub file_path: Option<String>,
+    pub line_start: Option<i64>,
+    pub column_start: Option<i64>,
+    pub line_end: Option<i64>,
+    pub rendered: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl FromRow for DiagnosticRecord {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            change_id: row.get(0)?,
+            session_id: row.get(1)?,
+            source: row.get(2)?,
+            run_id: row.get(3)?,
+            level: row.get(4)?,
+            code: row.get(5)?,
+            message: row.get(6)?,
+            file_path: row.get(7)?,
+            line_start: row.get(8)?,
+            column_start: row.get(9)?,
+            line_end: row.get(10)?,
+            rendered: row.get(11)?,
+            created_at: row.get(12)?,
+        })
+    }
+}
+
+impl DiagnosticRecord {
+    pub fn format_diagnostic(&self) -> String {
+        let code = self
+            .code
+            .as_deref()
+            .map(|c| format!("[{c}]"))
+            .unwrap_or_default();
+        let location = match (&self.file_path, self.line_start, self.column_start) {
+            (Some(path), Some(line), Some(column)) => format!(" `{path}:{line}:{column}`"),
+            (Some(path), _, _) => format!(" `{path}`"),
+            _ => String::new(),
+        };
+        let rendered = self
+            .rendered
+            .as_deref()
+            .map(|r| format!("\n\n```text\n{}\n```", r.trim_end()))
+            .unwrap_or_default();
+        let origin = match &self.change_id {
+            Some(change_id) => format!("change {change_id}"),
+            None => self.source.clone(),
+        };
+        format!(
+            "**{}{code}**{location} ({}, {origin}): {}{rendered}",
+            self.level, self.created_at, self.message
+        )
+    }
+
+    // format_finding - one line per finding, for listings and review prompts
+    pub fn format_finding(&self) -> String {
+        let location = match (&self.file_path, self.line_start) {
+            (Some(path), Some(line)) => format!("`{path}:{line}`"),
+            (Some(path), None) => format!("`{path}`"),
+            _ => "(no location)".to_string(),
+        };
+        let code = self.code.as_deref().unwrap_or("-");
+        format!("- {location} `{code}` ({}): {}", self.level, self.message)
+    }
+}
+
+/// How often one diagnostic code came up, for `recent_diagnostics`.
+#[derive(Debug, Clone)]
+pub struct DiagnosticCount {
+    pub level: String,
+    pub code: Option<String>,
+    pub count: i64,
+    pub last_seen: DateTime<Utc>,
+    pub message: String, // the most recent message for the code
+}
+
+impl FromRow for DiagnosticCount {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            level: row.get(0)?,
+            code: row.get(1)?,
+            count: row.get(2)?,
+            last_seen: row.get(3)?,
+            message: row.get(4)?,
+        })
+    }
+}
+
+/// A cached `rustc --explain` page.
+#[derive(Debug, Clone)]
+pub struct ErrorExplanation {
+    pub code: String,
+    pub explanation: String, // markdown, as rustc prints it
+    pub rustc_version: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl FromRow for ErrorExplanation {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            code: row.get(0)?,
+            explanation: row.get(1)?,
+            rustc_version: row.get(2)?,
+            fetched_at: row.get(3)?,
+        })
+    }
+}
+
+/// One rustc error code or clippy lint across the whole history, for the
+/// learning report.
+#[derive(Debug, Clone)]
+pub struct CodeSummary {
+    pub code: String,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    pub hits: i64,         // saves (or clippy runs) that reported it
+    pub weeks: i64,        // distinct weeks it showed up in
+    pub checks_since: i64, // on-save checks run after it last showed up
+    pub message: String,   // the most recent message for it
+}
+
+impl FromRow for CodeSummary {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            code: row.get(0)?,
+            first_seen: row.get(1)?,
+            last_seen: row.get(2)?,
+            hits: row.get(3)?,
+            weeks: row.get(4)?,
+            checks_since: row.get(5)?,
+            message: row.get(6)?,
+        })
+    }
+}
+
+/// On-save checks in one week (starting Monday), and how many of them failed.
+#[derive(Debug, Clone)]
+pub struct WeeklyChecks {
+    pub week: String, // YYYY-MM-DD of the Monday
+    pub checks: i64,
+    pub failing: i64,
+}
+
+impl FromRow for WeeklyChecks {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            week: row.get(0)?,
+            checks: row.get(1)?,
+            failing: row.get(2)?,
+        })
+    }
+}
+
+/// How often one code came up in one week.
+#[derive(Debug, Clone)]
+pub struct WeeklyCode {
+    pub week: String,
+    pub code: String,
+    pub hits: i64,
+}
+
+impl FromRow for WeeklyCode {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            week: row.get(0)?,
+            code: row.get(1)?,
+            hits: row.get(2)?,
+        })
+    }
+}
+
+/// A cached docs.rs response, revalidated with its ETag or Last-Modified
+/// once `expires_at` passes.
+#[derive(Debug, Clone)]
+pub struct HttpCacheEntry {
+    pub url: String,
+    pub crate_name: String,
+    pub version: String, // as requested, so `latest` is its own entry
+    pub body: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl FromRow for HttpCacheEntry {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            url: row.get(0)?,
+            crate_name: row.get(1)?,
+            version: row.get(2)?,
+            body: row.get(3)?,
+            etag: row.get(4)?,
+            last_modified: row.get(5)?,
+            fetched_at: row.get(6)?,
+            expires_at: row.get(7)?,
+        })
+    }
+}
+
+/// One item in the local doc index, read from `target/doc` or rustdoc JSON.
+#[derive(Debug, Clone)]
+pub struct DocIndexRecord {
+    pub crate_name: String,
+    pub version: Option<String>, // from Cargo.lock or the JSON, when known
+    pub path: String,            // inner::Widget, relative to the crate
+    pub kind: String,            // struct, trait, fn, ... as in rustdoc's file names
+    pub location: String,        // the item's html page, or the json file it came from
+    pub source: String,          // html or json
+    pub docs: Option<String>,    // rendered markdown, json items only
+    pub indexed_at: DateTime<Utc>,
+}
+
+impl FromRow for DocIndexRecord {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            crate_name: row.get(0)?,
+            version: row.get(1)?,
+            path: row.get(2)?,
+            kind: row.get(3)?,
+            location: row.get(4)?,
+            source: row.get(5)?,
+            docs: row.get(6)?,
+            indexed_at: row.get(7)?,
+        })
+    }
+}
+
+/// A crate in the local doc index.
+#[derive(Debug, Clone)]
+pub struct DocIndexCrate {
+    pub crate_name: String,
+    pub version: Option<String>,
+    pub source: String,
+    pub items: i64,
+    pub indexed_at: DateTime<Utc>,
+}
+
+impl FromRow for DocIndexCrate {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            crate_name: row.get(0)?,
+            version: row.get(1)?,
+            source: row.get(2)?,
+            items: row.get(3)?,
+            indexed_at: row.get(4)?,
+        })
+    }
+}
+
+impl DocIndexCrate {
+    pub fn format_row(&self) -> String {
+        format!(
+            "| `{}` | {} | {} | {} | {} |",
+            self.crate_name,
+            self.version.as_deref().unwrap_or("-"),
+            self.source,
+            self.items,
+            self.indexed_at.format("%Y-%m-%d %H:%M")
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReviewRecord {
+    pub id: i64,
+    pub file_path: String,
+    pub content_hash: String, // sha256 of the file contents that were reviewed
+    pub review: String,
+    pub backend: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl FromRow for ReviewRecord {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            file_path: row.get(1)?,
+            content_hash: row.get(2)?,
+            review: row.get(3)?,
+            backend: row.get(4)?,
+            created_at: row.get(5)?,
+        })
+    }
+}
+
+impl ReviewRecord {
+    pub fn format_summary(&self) -> String {
+        let preview: String = self.review.chars().take(200).collect();
+        format!(
+            "**ID {}** `{}` ({}, {}, content {}):\n\n{}{}",
+            self.id,
+            self.file_path,
+            self.created_at,
+            self.backend,
+            &self.content_hash[..self.content_hash.len().min(12)],
+            preview,
+            if preview.len() < self.review.len() {
+                "…"
+            } else {
+                ""
+            }
         )
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct ApiCallRecord {
+    pub tool: String, // MCP tool that triggered the call
+    pub backend: String,
+    pub model: String,
+    pub file_path: Option<String>,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub latency_ms: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// How `usage_summary` buckets API calls.
+#[derive(Debug, Clone, Copy)]
+pub enum UsageGrouping {
+    Day,
+    Tool,
+    File,
+}
+
+#[derive(Debug, Clone)]
+pub struct UsageRow {
+    pub key: String, // day, tool name or file path depending on the grouping
+    pub model: String,
+    pub calls: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub total_latency_ms: i64,
+}
+
+impl FromRow for UsageRow {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            key: row.get(0)?,
+            model: row.get(1)?,
+            calls: row.get(2)?,
+            input_tokens: row.get(3)?,
+            output_tokens: row.get(4)?,
+            total_latency_ms: row.get(5)?,
+        })
+    }
+}
+
+/// Schema version and per-table row counts, for the `store_info` tool.
+#[derive(Debug, Clone)]
+pub struct StoreInfo {
+    pub path: PathBuf,
+    pub schema_version: i64,
+    pub latest_version: i64,
+    pub tables: Vec<(String, i64)>,
+    pub http_cache_bytes: i64, // bodies of cached docs.rs responses
+}
+
+impl StoreInfo {
+    pub fn format_summary(&self) -> String {
+        let mut out = format!(
+            "**Database**: {}\n**Schema version**: {} (latest {})\n\n| Table | Rows |\n|---|---:|\n",
+            self.path.display(),
+            self.schema_version,
+            self.latest_version
+        );
+        for (table, rows) in &self.tables {
+            out.push_str(&format!("| {table} | {rows} |\n"));
+        }
+        out.push_str(&format!(
+            "\n**HTTP cache**: {:.1} MiB of cached docs\n",
+            self.http_cache_bytes as f64 / (1024.0 * 1024.0)
+        ));
+        out
+    }
+}
+
 #[derive(Debug)]
 pub struct TutorStore {
     conn: Arc<Mutex<rusqlite::Connection>>,
+    path: PathBuf,
 }
 
 impl TutorStore {
@@ -149,43 +779,15 @@ impl TutorStore {
         )?;
 
         // create a connection to the database here to be able to creat the tables
-        let conn = rusqlite::Connection::open(path)?;
+        let mut conn = rusqlite::Connection::open(path)?;
 
-        // create tables - this is idempotent so we can safely call it repeatedly
-        conn.execute_batch(
-            r##"
-            CREATE TABLE IF NOT EXISTS scaffolds (
-                id INTEGER PRIMARY KEY,
-                description TEXT NOT NULL,
-                content TEXT NOT NULL,
-                created_at TEXT NOT NULL
-            )
-        "##,
-        )
-        .context("failed to create scaffold table")?;
-
-        conn.execute_batch(
-            r##"
-            CREATE TABLE IF NOT EXISTS file_changes (
-                id INTEGER PRIMARY KEY,
-                file_path TEXT NOT NULL,
-                hunk_idx INTEGER NOT NULL,
-                change_id TEXT NOT NULL,
-                old_start INTEGER NOT NULL,
-                old_count INTEGER NOT NULL,
-                new_start INTEGER NOT NULL,
-                new_count INTEGER NOT NULL,
-                before_lines TEXT NOT NULL,
-                after_lines TEXT NOT NULL,
-                changed_at TEXT NOT NULL
-
-            ) 
-        "##,
-        )
-        .context("failed to create file_changes table")?;
+        // bring the schema up to date - already applied migrations are skipped
+        migrations::migrate(&mut conn)
+            .with_context(|| format!("failed to migrate {}", path.display()))?;
 
         Ok(Self {
             conn: Arc::new(Mutex::new(conn)),
+            path: path.to_path_buf(),
         })
     }
 
@@ -241,25 +843,52 @@ impl TutorStore {
 
         Ok(conn.last_insert_rowid())
     }
-    // search - this searches for scaffolds that match the query
-    pub fn search_scaffolds(&self, query: &str) -> Result<Vec<ScaffoldRecord>> {
+
+    // search - this runs a ranked full-text search over scaffold descriptions
+    // and contents. Description hits weigh more than hits buried in the plan
+    pub fn search_scaffolds(
+        &self,
+        query: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<ScaffoldMatch>> {
+        let Some(fts_query) = Self::fts_query(query) else {
+            return Ok(Vec::new());
+        };
+
         let conn = self.conn.lock().expect("store lock poisoned");
         let mut stmt = conn
             .prepare(
                 r##"
-           SELECT id, description, content, created_at
-           FROM scaffolds
-           WHERE description LIKE ?1
-           ORDER BY created_at DESC
-           LIMIT 10
-            "##,
+                SELECT s.id,
+                       highlight(scaffolds_fts, 0, '**', '**'),
+                       snippet(scaffolds_fts, 1, '**', '**', '…', 24),
+                       s.created_at
+                FROM scaffolds_fts
+                JOIN scaffolds s ON s.id = scaffolds_fts.rowid
+                WHERE scaffolds_fts MATCH ?1
+                ORDER BY bm25(scaffolds_fts, 10.0, 1.0)
+                LIMIT ?2 OFFSET ?3
+                "##,
             )
             .context("failed to prepare search query")?;
 
-        Self::collect_rows(&mut stmt, params![format!("%{}%", query)])
+        Self::collect_rows(&mut stmt, params![fts_query, limit, offset])
             .context("failed to collect search results")
     }
 
+    // fts_query - this quotes every word of a free-form query as an FTS5 prefix
+    // term, so punctuation like `serde::Deserialize` or a stray `"` can't turn
+    // into query syntax errors. Words are ANDed together
+    fn fts_query(query: &str) -> Option<String> {
+        let terms: Vec<String> = query
+            .split_whitespace()
+            .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+            .collect();
+
+        (!terms.is_empty()).then(|| terms.join(" "))
+    }
+
     // get - this gets a single scaffold by id
     pub fn get_scaffold_by_id(&self, id: i64) -> Result<Option<ScaffoldRecord>> {
         let conn = self.conn.lock().expect("store lock poisoned");
@@ -280,8 +909,8 @@ impl TutorStore {
         }
     }
 
-    // list_recent - this lists the most recent scaffolds
-    pub fn list_recent_scaffolds(&self, limit: i64) -> Result<Vec<ScaffoldRecord>> {
+    // list_recent - this lists the most recent scaffolds, newest first
+    pub fn list_recent_scaffolds(&self, limit: i64, offset: i64) -> Result<Vec<ScaffoldRecord>> {
         let conn = self.conn.lock().expect("store lock poisoned");
 
         let mut stmt = conn
@@ -290,12 +919,12 @@ impl TutorStore {
                 SELECT id, description, content, created_at
                 FROM scaffolds
                 ORDER BY created_at DESC
-                LIMIT ?1
+                LIMIT ?1 OFFSET ?2
                 "##,
             )
             .context("failed to prepare list query")?;
 
-        Self::collect_rows(&mut stmt, [limit])
+        Self::collect_rows(&mut stmt, [limit, offset])
     }
 
     // FILE CHANGES
@@ -306,8 +935,8 @@ impl TutorStore {
 
         conn
             .execute(r##"
-            INSERT INTO file_changes (file_path, hunk_idx, change_id, old_start, old_count, new_start, new_count, before_lines, after_lines, changed_at)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+            INSERT INTO file_changes (file_path, hunk_idx, change_id, old_start, old_count, new_start, new_count, before_lines, after_lines, changed_at, kind, old_path, new_text, content_hash, lines_added, lines_removed, session_id)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)
             "##,
             params![
                 file_change.file_path,
@@ -320,6 +949,13 @@ impl TutorStore {
                 file_change.before_lines,
                 file_change.after_lines,
                 file_change.changed_at,
+                file_change.kind,
+                file_change.old_path,
+                file_change.new_text,
+                file_change.content_hash,
+                file_change.lines_added,
+                file_change.lines_removed,
+                file_change.session_id,
             ])
             .context("failed to save file change")?;
 
@@ -335,7 +971,7 @@ impl TutorStore {
         let mut stmt = conn
             .prepare(
                 r##"
-            SELECT id, file_path, hunk_idx, change_id, old_start, old_count, new_start, new_count, before_lines, after_lines, changed_at
+            SELECT id, file_path, hunk_idx, change_id, old_start, old_count, new_start, new_count, before_lines, after_lines, changed_at, kind, old_path, new_text, content_hash, lines_added, lines_removed, session_id
             FROM file_changes
             WHERE file_path = ?1
             ORDER BY changed_at DESC
@@ -354,9 +990,13 @@ impl TutorStore {
         let mut stmt = conn
             .prepare(
                 r##"
-                    SELECT change_id, file_path, changed_at, COUNT(*) as hunk_count
+                    SELECT change_id, file_path, changed_at, COUNT(*) as hunk_count, 0, kind, old_path
                     FROM file_changes
                     GROUP BY change_id
+                    UNION ALL
+                    SELECT change_id, file_path, changed_at, 0, COUNT(*), 'modify', NULL
+                    FROM dependency_changes
+                    GROUP BY change_id
                     ORDER BY changed_at DESC
                     LIMIT ?1
                 "##,
@@ -366,12 +1006,28 @@ impl TutorStore {
         Self::collect_rows(&mut stmt, [limit]).context("failed to collect list results")
     }
 
+    // latest_file_change_id - this is the newest save that recorded hunks,
+    // skipping Cargo.toml and Cargo.lock saves, which only record dependencies
+    pub fn latest_file_change_id(&self) -> Result<Option<String>> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+
+        match conn.query_row(
+            "SELECT change_id FROM file_changes ORDER BY changed_at DESC, id DESC LIMIT 1",
+            [],
+            |row| row.get(0),
+        ) {
+            Ok(change_id) => Ok(Some(change_id)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e).context("failed to get latest change id"),
+        }
+    }
+
     pub fn get_changes_for_change_id(&self, change_id: &str) -> Result<Vec<FileChangeRecord>> {
         let conn = self.conn.lock().expect("store lock poisoned");
         let mut stmt = conn
             .prepare(
                r##"
-               SELECT id, file_path, hunk_idx, change_id, old_start, old_count, new_start, new_count, before_lines, after_lines, changed_at
+               SELECT id, file_path, hunk_idx, change_id, old_start, old_count, new_start, new_count, before_lines, after_lines, changed_at, kind, old_path, new_text, content_hash, lines_added, lines_removed, session_id
                FROM file_changes
                WHERE change_id = ?1
                ORDER BY changed_at DESC
@@ -380,4 +1036,1056 @@ impl TutorStore {
 
         Self::collect_rows(&mut stmt, params![change_id]).context("failed to collect get results")
     }
+
+    // get_changes_between - this gets every hunk saved in [since, until), oldest first
+    pub fn get_changes_between(
+        &self,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<FileChangeRecord>> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+        let mut stmt = conn
+            .prepare(
+                r##"
+            SELECT id, file_path, hunk_idx, change_id, old_start, old_count, new_start, new_count, before_lines, after_lines, changed_at, kind, old_path, new_text, content_hash, lines_added, lines_removed, session_id
+            FROM file_changes
+            WHERE changed_at >= ?1 AND changed_at < ?2
+            ORDER BY changed_at ASC, hunk_idx ASC
+            "##,
+            )
+            .context("failed to prepare get query")?;
+
+        Self::collect_rows(&mut stmt, params![since, until])
+            .context("failed to collect get results")
+    }
+
+    // get_change_time - this finds when a save happened and which file it touched
+    pub fn get_change_time(&self, change_id: &str) -> Result<Option<(String, DateTime<Utc>)>> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+        let result = conn.query_row(
+            r##"
+            SELECT file_path, changed_at FROM file_changes WHERE change_id = ?1
+            UNION ALL
+            SELECT file_path, changed_at FROM dependency_changes WHERE change_id = ?1
+            LIMIT 1
+            "##,
+            params![change_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        );
+
+        match result {
+            Ok(found) => Ok(Some(found)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e).context("failed to look up change"),
+        }
+    }
+
+    // get_history_after - this gets the hunks that touched `file_path` in
+    // (since, until], oldest first, including renames that moved it away
+    pub fn get_history_after(
+        &self,
+        file_path: &str,
+        since: Option<DateTime<Utc>>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<FileChangeRecord>> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+        let mut stmt = conn
+            .prepare(
+                r##"
+            SELECT id, file_path, hunk_idx, change_id, old_start, old_count, new_start, new_count, before_lines, after_lines, changed_at, kind, old_path, new_text, content_hash, lines_added, lines_removed, session_id
+            FROM file_changes
+            WHERE (file_path = ?1 OR (kind = 'rename' AND old_path = ?1))
+              AND (?2 IS NULL OR changed_at > ?2)
+              AND changed_at <= ?3
+            ORDER BY changed_at ASC, id ASC
+            "##,
+            )
+            .context("failed to prepare history query")?;
+
+        Self::collect_rows(&mut stmt, params![file_path, since, until])
+            .context("failed to collect history")
+    }
+
+    // SNAPSHOTS
+
+    pub fn save_snapshot(&self, snapshot: &SnapshotRecord) -> Result<i64> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+
+        conn.execute(
+            r##"
+                INSERT INTO file_snapshots (file_path, change_id, reason, content, content_hash, created_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "##,
+            params![
+                snapshot.file_path,
+                snapshot.change_id,
+                snapshot.reason,
+                snapshot.content,
+                snapshot.content_hash,
+                snapshot.created_at,
+            ],
+        )
+        .context("failed to save snapshot")?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    // latest_snapshot - this gets the newest snapshot of `file_path` taken at or before `at`
+    pub fn latest_snapshot(
+        &self,
+        file_path: &str,
+        at: DateTime<Utc>,
+    ) -> Result<Option<SnapshotRecord>> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+        let mut stmt = conn
+            .prepare(
+                r##"
+            SELECT file_path, change_id, reason, content, content_hash, created_at
+            FROM file_snapshots
+            WHERE file_path = ?1 AND created_at <= ?2
+            ORDER BY created_at DESC, id DESC
+            LIMIT 1
+            "##,
+            )
+            .context("failed to prepare snapshot query")?;
+
+        match stmt.query_row(params![file_path, at], SnapshotRecord::from_row) {
+            Ok(snapshot) => Ok(Some(snapshot)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e).context("failed to get snapshot"),
+        }
+    }
+
+    // saves_since_snapshot - this counts the saves of `file_path` after its newest snapshot
+    pub fn saves_since_snapshot(&self, file_path: &str) -> Result<i64> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+        conn.query_row(
+            r##"
+            SELECT COUNT(DISTINCT change_id)
+            FROM file_changes
+            WHERE file_path = ?1
+              AND changed_at > COALESCE(
+                  (SELECT MAX(created_at) FROM file_snapshots WHERE file_path = ?1), '')
+            "##,
+            params![file_path],
+            |row| row.get(0),
+        )
+        .context("failed to count saves since snapshot")
+    }
+
+    // latest_content_hashes - this gets the last known content hash of every
+    // file, from whichever of its newest snapshot or newest save came later
+    pub fn latest_content_hashes(&self) -> Result<HashMap<String, String>> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+        let mut stmt = conn
+            .prepare(
+                r##"
+            SELECT file_path, content_hash FROM (
+                SELECT file_path, content_hash,
+                       ROW_NUMBER() OVER (PARTITION BY file_path ORDER BY at DESC, snapshot DESC) AS rn
+                FROM (
+                    SELECT file_path, content_hash, created_at AS at, 1 AS snapshot FROM file_snapshots
+                    UNION ALL
+                    SELECT file_path, content_hash, changed_at, 0 FROM file_changes
+                    WHERE content_hash IS NOT NULL
+                )
+            )
+            WHERE rn = 1
+            "##,
+            )
+            .context("failed to prepare content hash query")?;
+
+        let hashes = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()
+            .context("failed to collect content hashes")?;
+
+        Ok(hashes)
+    }
+
+    // DEPENDENCY CHANGES
+
+    // save_dependency_change - this records one crate-level change from a manifest or lockfile save
+    pub fn save_dependency_change(&self, change: &DependencyChangeRecord) -> Result<i64> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+
+        conn.execute(
+            r##"
+                INSERT INTO dependency_changes
+                    (change_id, file_path, crate_name, section, kind, old_version, new_version, changed_at, session_id)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            "##,
+            params![
+                change.change_id,
+                change.file_path,
+                change.crate_name,
+                change.section,
+                change.kind,
+                change.old_version,
+                change.new_version,
+                change.changed_at,
+                change.session_id,
+            ],
+        )
+        .context("failed to save dependency change")?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    pub fn get_dependency_changes_for_change_id(
+        &self,
+        change_id: &str,
+    ) -> Result<Vec<DependencyChangeRecord>> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+        let mut stmt = conn
+            .prepare(
+                r##"
+                SELECT change_id, file_path, crate_name, section, kind, old_version, new_version, changed_at, session_id
+                FROM dependency_changes
+                WHERE change_id = ?1
+                ORDER BY section, crate_name
+                "##,
+            )
+            .context("failed to prepare dependency query")?;
+
+        Self::collect_rows(&mut stmt, params![change_id])
+            .context("failed to collect dependency changes")
+    }
+
+    // SESSIONS
+
+    // start_session - this opens a new session with its first save
+    pub fn start_session(
+        &self,
+        at: DateTime<Utc>,
+        lines_added: i64,
+        lines_removed: i64,
+    ) -> Result<i64> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+
+        conn.execute(
+            r##"
+                INSERT INTO sessions (started_at, ended_at, save_count, lines_added, lines_removed)
+                VALUES (?1, ?1, 1, ?2, ?3)
+            "##,
+            params![at, lines_added, lines_removed],
+        )
+        .context("failed to start session")?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    // record_session_activity - this extends a session with another save
+    pub fn record_session_activity(
+        &self,
+        id: i64,
+        at: DateTime<Utc>,
+        lines_added: i64,
+        lines_removed: i64,
+    ) -> Result<()> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+
+        conn.execute(
+            r##"
+                UPDATE sessions
+                SET ended_at = ?2,
+                    save_count = save_count + 1,
+                    lines_added = lines_added + ?3,
+                    lines_removed = lines_removed + ?4
+                WHERE id = ?1
+            "##,
+            params![id, at, lines_added, lines_removed],
+        )
+        .context("failed to update session")?;
+
+        Ok(())
+    }
+
+    // list_sessions - this lists sessions newest first, with how many files each touched
+    pub fn list_sessions(&self, limit: i64, offset: i64) -> Result<Vec<SessionRecord>> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+        let mut stmt = conn
+            .prepare(&format!(
+                "{SESSION_SELECT} ORDER BY s.started_at DESC LIMIT ?1 OFFSET ?2"
+            ))
+            .context("failed to prepare session list query")?;
+
+        Self::collect_rows(&mut stmt, params![limit, offset]).context("failed to collect sessions")
+    }
+
+    // latest_session - this gets the most recent session, if any
+    pub fn latest_session(&self) -> Result<Option<SessionRecord>> {
+        Ok(self.list_sessions(1, 0)?.into_iter().next())
+    }
+
+    // get_session - this gets a single session by id
+    pub fn get_session(&self, id: i64) -> Result<Option<SessionRecord>> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+        let mut stmt = conn
+            .prepare(&format!("{SESSION_SELECT} WHERE s.id = ?1"))
+            .context("failed to prepare session query")?;
+
+        match stmt.query_row(params![id], SessionRecord::from_row) {
+            Ok(record) => Ok(Some(record)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e).context("failed to return session"),
+        }
+    }
+
+    // get_session_files - this totals saves and churn per file within a session
+    pub fn get_session_files(&self, id: i64) -> Result<Vec<SessionFileRow>> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+        let mut stmt = conn
+            .prepare(
+                r##"
+                SELECT file_path, COUNT(DISTINCT change_id), SUM(lines_added), SUM(lines_removed)
+                FROM (
+                    SELECT file_path, change_id, lines_added, lines_removed
+                    FROM file_changes
+                    WHERE session_id = ?1
+                    UNION ALL
+                    SELECT file_path, change_id, 0, 0
+                    FROM dependency_changes
+                    WHERE session_id = ?1
+                )
+                GROUP BY file_path
+                ORDER BY SUM(lines_added) + SUM(lines_removed) DESC, file_path
+                "##,
+            )
+            .context("failed to prepare session files query")?;
+
+        Self::collect_rows(&mut stmt, params![id]).context("failed to collect session files")
+    }
+
+    // get_session_saves - this lists every save in a session, oldest first
+    pub fn get_session_saves(&self, id: i64) -> Result<Vec<SaveEventSummary>> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+        let mut stmt = conn
+            .prepare(
+                r##"
+                    SELECT change_id, file_path, changed_at, COUNT(*) as hunk_count, 0, kind, old_path
+                    FROM file_changes
+                    WHERE session_id = ?1
+                    GROUP BY change_id
+                    UNION ALL
+                    SELECT change_id, file_path, changed_at, 0, COUNT(*), 'modify', NULL
+                    FROM dependency_changes
+                    WHERE session_id = ?1
+                    GROUP BY change_id
+                    ORDER BY changed_at
+                "##,
+            )
+            .context("failed to prepare session saves query")?;
+
+        Self::collect_rows(&mut stmt, params![id]).context("failed to collect session saves")
+    }
+
+    // get_session_dependency_changes - this lists the dependency changes made in a session
+    pub fn get_session_dependency_changes(&self, id: i64) -> Result<Vec<DependencyChangeRecord>> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+        let mut stmt = conn
+            .prepare(
+                r##"
+                SELECT change_id, file_path, crate_name, section, kind, old_version, new_version, changed_at, session_id
+                FROM dependency_changes
+                WHERE session_id = ?1
+                ORDER BY changed_at, section, crate_name
+                "##,
+            )
+            .context("failed to prepare dependency query")?;
+
+        Self::collect_rows(&mut stmt, params![id]).context("failed to collect dependency changes")
+    }
+
+    // DIAGNOSTICS
+
+    // save_check_run - this records one on-save `cargo check` (or clippy)
+    // run and everything it reported
+    pub fn save_check_run(
+        &self,
+        change_id: &str,
+        session_id: Option<i64>,
+        source: &str,
+        ran_at: DateTime<Utc>,
+        diagnostics: &[DiagnosticRecord],
+    ) -> Result<()> {
+        let mut conn = self.conn.lock().expect("store lock poisoned");
+        let tx = conn.transaction()?;
+
+        let count = |level: &str| diagnostics.iter().filter(|d| d.level == level).count() as i64;
+        tx.execute(
+            r##"
+                INSERT INTO check_runs (change_id, session_id, source, error_count, warning_count, ran_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "##,
+            params![
+                change_id,
+                session_id,
+                source,
+                count("error"),
+                count("warning"),
+                ran_at
+            ],
+        )
+        .context("failed to save check run")?;
+
+        for d in diagnostics {
+            Self::insert_diagnostic(&tx, d, d.run_id)?;
+        }
+
+        tx.commit().context("failed to commit check run")
+    }
+
+    // save_clippy_run - this records a clippy run over the crate at
+    // `crate_root` and its findings, returning the run id
+    pub fn save_clippy_run(
+        &self,
+        crate_root: &str,
+        ran_at: DateTime<Utc>,
+        findings: &[DiagnosticRecord],
+    ) -> Result<i64> {
+        let mut conn = self.conn.lock().expect("store lock poisoned");
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            r##"
+                INSERT INTO clippy_runs (crate_root, finding_count, ran_at)
+                VALUES (?1, ?2, ?3)
+            "##,
+            params![crate_root, findings.len() as i64, ran_at],
+        )
+        .context("failed to save clippy run")?;
+        let run_id = tx.last_insert_rowid();
+
+        for d in findings {
+            Self::insert_diagnostic(&tx, d, Some(run_id))?;
+        }
+
+        tx.commit().context("failed to commit clippy run")?;
+        Ok(run_id)
+    }
+
+    // latest_clippy_run - this gets the id and time of the newest clippy run over a crate
+    pub fn latest_clippy_run(&self, crate_root: &str) -> Result<Option<(i64, DateTime<Utc>)>> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+        let mut stmt = conn
+            .prepare(
+                r##"
+                SELECT id, ran_at
+                FROM clippy_runs
+                WHERE crate_root = ?1
+                ORDER BY ran_at DESC
+                LIMIT 1
+                "##,
+            )
+            .context("failed to prepare clippy run query")?;
+
+        match stmt.query_row(params![crate_root], |row| Ok((row.get(0)?, row.get(1)?))) {
+            Ok(run) => Ok(Some(run)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e).context("failed to return clippy run"),
+        }
+    }
+
+    // get_clippy_findings - this lists a run's findings, optionally for one file
+    pub fn get_clippy_findings(
+        &self,
+        run_id: i64,
+        file_path: Option<&str>,
+    ) -> Result<Vec<DiagnosticRecord>> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+        let mut stmt = conn
+            .prepare(
+                r##"
+                SELECT change_id, session_id, source, run_id, level, code, message, file_path, line_start, column_start, line_end, rendered, created_at
+                FROM diagnostics
+                WHERE run_id = ?1 AND (?2 IS NULL OR file_path = ?2)
+                ORDER BY file_path, line_start, column_start
+                "##,
+            )
+            .context("failed to prepare clippy findings query")?;
+
+        Self::collect_rows(&mut stmt, params![run_id, file_path])
+            .context("failed to collect clippy findings")
+    }
+
+    fn insert_diagnostic(
+        conn: &rusqlite::Connection,
+        d: &DiagnosticRecord,
+        run_id: Option<i64>,
+    ) -> Result<()> {
+        conn.execute(
+                r##"
+                    INSERT INTO diagnostics
+                        (change_id, session_id, source, run_id, level, code, message, file_path, line_start, column_start, line_end, rendered, created_at)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+                "##,
+                params![
+                    d.change_id,
+                    d.session_id,
+                    d.source,
+                    run_id,
+                    d.level,
+                    d.code,
+                    d.message,
+                    d.file_path,
+                    d.line_start,
+                    d.column_start,
+                    d.line_end,
+                    d.rendered,
+                    d.created_at,
+                ],
+            )
+            .context("failed to save diagnostic")?;
+
+        Ok(())
+    }
+
+    // recent_diagnostics - this lists the newest diagnostics, optionally for
+    // one file, level or source
+    pub fn recent_diagnostics(
+        &self,
+        file_path: Option<&str>,
+        level: Option<&str>,
+        source: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<DiagnosticRecord>> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+        let mut stmt = conn
+            .prepare(
+                r##"
+                SELECT change_id, session_id, source, run_id, level, code, message, file_path, line_start, column_start, line_end, rendered, created_at
+                FROM diagnostics
+                WHERE (?1 IS NULL OR file_path = ?1)
+                  AND (?2 IS NULL OR level = ?2)
+                  AND (?3 IS NULL OR source = ?3)
+                ORDER BY created_at DESC, id
+                LIMIT ?4
+                "##,
+            )
+            .context("failed to prepare diagnostics query")?;
+
+        Self::collect_rows(&mut stmt, params![file_path, level, source, limit])
+            .context("failed to collect diagnostics")
+    }
+
+    // diagnostic_counts - this counts the checks or clippy runs each
+    // diagnostic code showed up in since `since`, most frequent first
+    pub fn diagnostic_counts(
+        &self,
+        file_path: Option<&str>,
+        level: Option<&str>,
+        source: Option<&str>,
+        since: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<DiagnosticCount>> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+        let mut stmt = conn
+            .prepare(
+                r##"
+                WITH matching AS (
+                    SELECT *, COALESCE(change_id, 'run ' || run_id) AS origin
+                    FROM diagnostics
+                    WHERE created_at >= ?1
+                      AND (?2 IS NULL OR file_path = ?2)
+                      AND (?3 IS NULL OR level = ?3)
+                      AND (?4 IS NULL OR source = ?4)
+                )
+                SELECT level, code, COUNT(DISTINCT origin), MAX(created_at),
+                       (SELECT message FROM matching m
+                        WHERE m.level = g.level
+                          AND COALESCE(m.code, m.message) = COALESCE(g.code, g.message)
+                        ORDER BY created_at DESC LIMIT 1)
+                FROM matching g
+                -- diagnostics without a code are told apart by their message
+                GROUP BY level, COALESCE(code, message)
+                ORDER BY COUNT(DISTINCT origin) DESC, MAX(created_at) DESC
+                LIMIT ?5
+                "##,
+            )
+            .context("failed to prepare diagnostic count query")?;
+
+        Self::collect_rows(&mut stmt, params![since, file_path, level, source, limit])
+            .context("failed to collect diagnostic counts")
+    }
+
+    // count_code_occurrences - this counts the checks or clippy runs that
+    // reported `code` since `since`, and over all time
+    pub fn count_code_occurrences(&self, code: &str, since: DateTime<Utc>) -> Result<(i64, i64)> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+
+        conn.query_row(
+            r##"
+                SELECT COUNT(DISTINCT CASE WHEN created_at >= ?2 THEN origin END),
+                       COUNT(DISTINCT origin)
+                FROM (
+                    SELECT created_at, COALESCE(change_id, 'run ' || run_id) AS origin
+                    FROM diagnostics
+                    WHERE code = ?1
+                )
+            "##,
+            params![code, since],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .context("failed to count diagnostics")
+    }
+
+    // get_diagnostics_for_code - this lists the newest diagnostics with one code
+    pub fn get_diagnostics_for_code(
+        &self,
+        code: &str,
+        limit: i64,
+    ) -> Result<Vec<DiagnosticRecord>> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+        let mut stmt = conn
+            .prepare(
+                r##"
+                SELECT change_id, session_id, source, run_id, level, code, message, file_path, line_start, column_start, line_end, rendered, created_at
+                FROM diagnostics
+                WHERE code = ?1
+                ORDER BY created_at DESC, id
+                LIMIT ?2
+                "##,
+            )
+            .context("failed to prepare diagnostics query")?;
+
+        Self::collect_rows(&mut stmt, params![code, limit]).context("failed to collect diagnostics")
+    }
+
+    // LEARNING
+
+    // get_code_summaries - this summarizes every rustc error code and clippy
+    // lint ever reported: when, how often, and how many checks ran since.
+    // Lints only count on-save runs of clippy, since `cargo check` can't see them
+    pub fn get_code_summaries(&self) -> Result<Vec<CodeSummary>> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+        let mut stmt = conn
+            .prepare(&format!(
+                r##"
+                WITH hits AS ({STRUGGLE_HITS}),
+                codes AS (
+                    SELECT code, MIN(created_at) AS first_seen, MAX(created_at) AS last_seen,
+                           COUNT(DISTINCT origin) AS hits, COUNT(DISTINCT week) AS weeks
+                    FROM hits
+                    GROUP BY code
+                )
+                SELECT code, first_seen, last_seen, hits, weeks,
+                       (SELECT COUNT(*) FROM check_runs r
+                        WHERE r.ran_at > c.last_seen
+                          AND (c.code NOT LIKE 'clippy::%' OR r.source = 'clippy')),
+                       (SELECT message FROM hits h WHERE h.code = c.code ORDER BY created_at DESC LIMIT 1)
+                FROM codes c
+                ORDER BY hits DESC, last_seen DESC
+                "##
+            ))
+            .context("failed to prepare code summary query")?;
+
+        Self::collect_rows(&mut stmt, []).context("failed to collect code summaries")
+    }
+
+    // get_weekly_checks - this counts on-save checks per week since `since`
+    pub fn get_weekly_checks(&self, since: DateTime<Utc>) -> Result<Vec<WeeklyChecks>> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+        let mut stmt = conn
+            .prepare(
+                r##"
+                SELECT date(ran_at, 'weekday 0', '-6 days') AS week, COUNT(*), SUM(error_count > 0)
+                FROM check_runs
+                WHERE ran_at >= ?1
+                GROUP BY week
+                ORDER BY week
+                "##,
+            )
+            .context("failed to prepare weekly check query")?;
+
+        Self::collect_rows(&mut stmt, params![since]).context("failed to collect weekly checks")
+    }
+
+    // get_weekly_codes - this counts each error code and lint per week since `since`
+    pub fn get_weekly_codes(&self, since: DateTime<Utc>) -> Result<Vec<WeeklyCode>> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+        let mut stmt = conn
+            .prepare(&format!(
+                r##"
+                WITH hits AS ({STRUGGLE_HITS})
+                SELECT week, code, COUNT(DISTINCT origin)
+                FROM hits
+                WHERE created_at >= ?1
+                GROUP BY week, code
+                ORDER BY week, COUNT(DISTINCT origin) DESC, code
+                "##
+            ))
+            .context("failed to prepare weekly code query")?;
+
+        Self::collect_rows(&mut stmt, params![since]).context("failed to collect weekly codes")
+    }
+
+    // ERROR EXPLANATIONS
+
+    // save_error_explanation - this caches an explanation, replacing any older copy
+    pub fn save_error_explanation(&self, explanation: &ErrorExplanation) -> Result<()> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+
+        conn.execute(
+            r##"
+                INSERT OR REPLACE INTO error_explanations (code, explanation, rustc_version, fetched_at)
+                VALUES (?1, ?2, ?3, ?4)
+            "##,
+            params![
+                explanation.code,
+                explanation.explanation,
+                explanation.rustc_version,
+                explanation.fetched_at,
+            ],
+        )
+        .context("failed to save error explanation")?;
+
+        Ok(())
+    }
+
+    // get_error_explanation - this gets a cached explanation by error code
+    pub fn get_error_explanation(&self, code: &str) -> Result<Option<ErrorExplanation>> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+        let mut stmt = conn
+            .prepare(
+                r##"
+                SELECT code, explanation, rustc_version, fetched_at
+                FROM error_explanations
+                WHERE code = ?1
+                "##,
+            )
+            .context("failed to prepare explanation query")?;
+
+        match stmt.query_row(params![code], ErrorExplanation::from_row) {
+            Ok(record) => Ok(Some(record)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e).context("failed to return error explanation"),
+        }
+    }
+
+    // HTTP CACHE
+
+    // save_http_cache - this stores a response, replacing any older copy of
+    // the url, and prunes responses that expired more than a grace period ago
+    pub fn save_http_cache(&self, entry: &HttpCacheEntry) -> Result<()> {
+        let mut conn = self.conn.lock().expect("store lock poisoned");
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            r##"
+                INSERT OR REPLACE INTO http_cache
+                    (url, crate_name, version, body, etag, last_modified, fetched_at, expires_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            "##,
+            params![
+                entry.url,
+                entry.crate_name,
+                entry.version,
+                entry.body,
+                entry.etag,
+                entry.last_modified,
+                entry.fetched_at,
+                entry.expires_at,
+            ],
+        )
+        .context("failed to save cached response")?;
+
+        tx.execute(
+            "DELETE FROM http_cache WHERE expires_at < ?1",
+            params![entry.fetched_at - chrono::Duration::days(HTTP_CACHE_GRACE_DAYS)],
+        )
+        .context("failed to prune cached responses")?;
+
+        tx.commit().context("failed to commit cached response")
+    }
+
+    // get_http_cache - this gets the cached response for a url, fresh or not
+    pub fn get_http_cache(&self, url: &str) -> Result<Option<HttpCacheEntry>> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+        let mut stmt = conn
+            .prepare(
+                r##"
+                SELECT url, crate_name, version, body, etag, last_modified, fetched_at, expires_at
+                FROM http_cache
+                WHERE url = ?1
+                "##,
+            )
+            .context("failed to prepare cache query")?;
+
+        match stmt.query_row(params![url], HttpCacheEntry::from_row) {
+            Ok(entry) => Ok(Some(entry)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e).context("failed to return cached response"),
+        }
+    }
+
+    // refresh_http_cache - this extends a cached response the server said is
+    // still current (304 Not Modified)
+    pub fn refresh_http_cache(
+        &self,
+        url: &str,
+        fetched_at: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<()> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+
+        conn.execute(
+            "UPDATE http_cache SET fetched_at = ?2, expires_at = ?3 WHERE url = ?1",
+            params![url, fetched_at, expires_at],
+        )
+        .context("failed to refresh cached response")?;
+
+        Ok(())
+    }
+
+    // DOC INDEX
+
+    // replace_doc_items - this swaps in a fresh index for one crate, dropping
+    // whatever an earlier build recorded for it
+    pub fn replace_doc_items(&self, crate_name: &str, items: &[DocIndexRecord]) -> Result<()> {
+        let mut conn = self.conn.lock().expect("store lock poisoned");
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "DELETE FROM doc_items WHERE crate_name = ?1",
+            params![crate_name],
+        )
+        .context("failed to clear doc index")?;
+
+        {
+            let mut stmt = tx.prepare(
+                r##"
+                INSERT INTO doc_items (crate_name, version, path, kind, location, source, docs, indexed_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                "##,
+            )?;
+            for item in items {
+                stmt.execute(params![
+                    item.crate_name,
+                    item.version,
+                    item.path,
+                    item.kind,
+                    item.location,
+                    item.source,
+                    item.docs,
+                    item.indexed_at,
+                ])
+                .context("failed to save doc item")?;
+            }
+        }
+
+        tx.commit().context("failed to commit doc index")
+    }
+
+    // get_doc_items - this lists indexed items, optionally for one crate or
+    // kind; callers match and rank them against the search themselves
+    pub fn get_doc_items(
+        &self,
+        crate_name: Option<&str>,
+        kind: Option<&str>,
+    ) -> Result<Vec<DocIndexRecord>> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+        let mut stmt = conn
+            .prepare(
+                r##"
+                SELECT crate_name, version, path, kind, location, source, docs, indexed_at
+                FROM doc_items
+                WHERE (?1 IS NULL OR crate_name = ?1)
+                  AND (?2 IS NULL OR kind = ?2)
+                ORDER BY crate_name, path
+                "##,
+            )
+            .context("failed to prepare doc index query")?;
+
+        Self::collect_rows(&mut stmt, params![crate_name, kind])
+            .context("failed to collect doc items")
+    }
+
+    // list_doc_crates - this lists the crates in the doc index
+    pub fn list_doc_crates(&self) -> Result<Vec<DocIndexCrate>> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+        let mut stmt = conn
+            .prepare(
+                r##"
+                SELECT crate_name, MAX(version), MAX(source), COUNT(*), MAX(indexed_at)
+                FROM doc_items
+                GROUP BY crate_name
+                ORDER BY crate_name
+                "##,
+            )
+            .context("failed to prepare doc index query")?;
+
+        Self::collect_rows(&mut stmt, []).context("failed to collect doc crates")
+    }
+
+    // REVIEWS
+
+    // save_review - this stores a finished review of a file
+    pub fn save_review(
+        &self,
+        file_path: &str,
+        content_hash: &str,
+        review: &str,
+        backend: &str,
+    ) -> Result<i64> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+
+        conn.execute(
+            r##"
+                INSERT INTO reviews (file_path, content_hash, review, backend, created_at)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+            "##,
+            params![file_path, content_hash, review, backend, Utc::now()],
+        )
+        .context("failed to save review")?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    // list_recent_reviews - this lists the most recent reviews, optionally for one file
+    pub fn list_recent_reviews(
+        &self,
+        file_path: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<ReviewRecord>> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+
+        let mut stmt = conn
+            .prepare(
+                r##"
+                SELECT id, file_path, content_hash, review, backend, created_at
+                FROM reviews
+                WHERE ?1 IS NULL OR file_path = ?1
+                ORDER BY created_at DESC
+                LIMIT ?2
+                "##,
+            )
+            .context("failed to prepare list query")?;
+
+        Self::collect_rows(&mut stmt, params![file_path, limit])
+    }
+
+    // get_review_by_id - this gets a single review by id
+    pub fn get_review_by_id(&self, id: i64) -> Result<Option<ReviewRecord>> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+        let mut stmt = conn
+            .prepare(
+                r##"
+            SELECT id, file_path, content_hash, review, backend, created_at
+            FROM reviews
+            WHERE id = ?1
+        "##,
+            )
+            .context("failed to prepare get query")?;
+
+        match stmt.query_row(params![id], ReviewRecord::from_row) {
+            Ok(record) => Ok(Some(record)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e).context("failed to return review"),
+        }
+    }
+
+    // API CALLS
+
+    // save_api_call - this records the token usage of a single LLM call
+    pub fn save_api_call(&self, call: &ApiCallRecord) -> Result<i64> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+
+        conn.execute(
+            r##"
+            INSERT INTO api_calls (tool, backend, model, file_path, input_tokens, output_tokens, latency_ms, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            "##,
+            params![
+                call.tool,
+                call.backend,
+                call.model,
+                call.file_path,
+                call.input_tokens,
+                call.output_tokens,
+                call.latency_ms,
+                call.created_at,
+            ],
+        )
+        .context("failed to save api call")?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    // usage_summary - this totals API calls since `since`, per grouping key and model
+    pub fn usage_summary(
+        &self,
+        grouping: UsageGrouping,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<UsageRow>> {
+        let key = match grouping {
+            UsageGrouping::Day => "substr(created_at, 1, 10)",
+            UsageGrouping::Tool => "tool",
+            UsageGrouping::File => "COALESCE(file_path, '(no file)')",
+        };
+
+        let conn = self.conn.lock().expect("store lock poisoned");
+        let mut stmt = conn
+            .prepare(&format!(
+                r##"
+                SELECT {key} AS key, model, COUNT(*), SUM(input_tokens), SUM(output_tokens), SUM(latency_ms)
+                FROM api_calls
+                WHERE created_at >= ?1
+                GROUP BY key, model
+                ORDER BY key DESC
+                "##
+            ))
+            .context("failed to prepare usage query")?;
+
+        Self::collect_rows(&mut stmt, params![since]).context("failed to collect usage results")
+    }
+
+    // STORE INFO
+
+    // store_info - this reports the schema version and how many rows each table holds
+    pub fn store_info(&self) -> Result<StoreInfo> {
+        let conn = self.conn.lock().expect("store lock poisoned");
+        let schema_version = migrations::schema_version(&conn)?;
+
+        let names: Vec<String> = conn
+            .prepare(
+                r##"
+                SELECT name FROM sqlite_master
+                WHERE type = 'table' AND name NOT LIKE 'sqlite_%'
+                  AND name NOT LIKE '%\_fts\_%' ESCAPE '\'
+                ORDER BY name
+                "##,
+            )?
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()
+            .context("failed to list tables")?;
+
+        let tables = names
+            .into_iter()
+            .map(|name| {
+                let rows = conn
+                    .query_row(&format!("SELECT COUNT(*) FROM \"{name}\""), [], |row| {
+                        row.get(0)
+                    })
+                    .with_context(|| format!("failed to count rows in {name}"))?;
+                Ok((name, rows))
+            })
+            .collect::<Result<_>>()?;
+
+        let http_cache_bytes = conn
+            .query_row(
+                "SELECT COALESCE(SUM(LENGTH(CAST(body AS BLOB))), 0) FROM http_cache",
+                [],
+                |row| row.get(0),
+            )
+            .context("failed to measure http cache")?;
+
+        Ok(StoreInfo {
+            path: self.path.clone(),
+            schema_version,
+            latest_version: migrations::latest_version(),
+            tables,
+            http_cache_bytes,
+        })
+    }
 }
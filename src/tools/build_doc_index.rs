@@ -0,0 +1,8 @@
+/// Input parameters for the `build_doc_index` tool.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct BuildDocIndexParams {
+    /// Only index this crate (a dependency or the project itself); indexes everything in target/doc when omitted
+    pub crate_name: Option<String>,
+    /// Generate rustdoc JSON with `cargo +nightly rustdoc` for `crate_name` (or the project's library) instead of reading existing `cargo doc` output
+    pub rustdoc_json: Option<bool>,
+}
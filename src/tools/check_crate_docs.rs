@@ -1,8 +1,18 @@
+/// Input parameters for the `check_crate_docs` tool.
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct CheckCrateDocsParams {
-    // Which crate to check for docs
+    /// Which crate to check for docs; `std`, `core` and `alloc` come from the installed toolchain
     pub crate_name: String,
-    // which type to look up
+    /// Which item to look up; close misspellings still match
     pub type_name: String,
+    /// Only include this kind of item, e.g. `struct`, `enum`, `trait`, `fn` or `macro`
+    pub kind: Option<String>,
+    /// Defaults to the version in the project's Cargo.lock, or `latest` for crates it doesn't lock.
+    /// For the standard library, a Rust release such as `1.80.0`, `stable` or `nightly`
     pub version: Option<String>,
+    /// `summary` (default) for the signature, first doc paragraph, method
+    /// signatures and trait names, or `full` for the whole page
+    pub detail: Option<String>,
+    /// Maximum number of other matches to list after the best one (defaults to 10)
+    pub limit: Option<i64>,
 }
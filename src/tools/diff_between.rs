@@ -0,0 +1,12 @@
+/// Input parameters for the `diff_between` tool.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct DiffBetweenParams {
+    /// Absolute path of the file
+    pub file_path: String,
+    /// Starting point: an RFC 3339 time or a change ID
+    pub from: String,
+    /// End point: an RFC 3339 time or a change ID (defaults to the file on disk)
+    pub to: Option<String>,
+    /// Lines of unchanged context around each hunk (defaults to 3)
+    pub context_lines: Option<usize>,
+}
@@ -0,0 +1,6 @@
+/// Input parameters for the `explain_error` tool.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ExplainErrorParams {
+    /// rustc error code, e.g. `E0502` (the `E` and leading zeros are optional)
+    pub code: String,
+}
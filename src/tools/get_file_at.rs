@@ -0,0 +1,10 @@
+/// Input parameters for the `get_file_at` tool.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetFileAtParams {
+    /// Absolute path of the file; optional when change_id is set
+    pub file_path: Option<String>,
+    /// RFC 3339 time to rebuild the file as of
+    pub at: Option<String>,
+    /// Rebuild the file as it was right after this save
+    pub change_id: Option<String>,
+}
@@ -0,0 +1,4 @@
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetReviewParams {
+    pub id: i64,
+}
@@ -0,0 +1,6 @@
+/// Input parameters for the `get_session` tool.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetSessionParams {
+    /// Session ID, as shown by `list_sessions`
+    pub id: i64,
+}
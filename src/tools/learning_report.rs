@@ -0,0 +1,6 @@
+/// Input parameters for the `learning_report` tool.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct LearningReportParams {
+    /// How many weeks back the weekly breakdown covers (defaults to 4, at most 520)
+    pub weeks: Option<i64>,
+}
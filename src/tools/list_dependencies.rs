@@ -0,0 +1,6 @@
+/// Input parameters for the `list_dependencies` tool.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ListDependenciesParams {
+    /// Only list one section, e.g. `dependencies`, `dev-dependencies` or `build-dependencies`
+    pub section: Option<String>,
+}
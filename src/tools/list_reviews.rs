@@ -0,0 +1,6 @@
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ListReviewsParams {
+    /// Only list reviews of this file
+    pub file_path: Option<String>,
+    pub limit: Option<i64>,
+}
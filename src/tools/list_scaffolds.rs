@@ -1,5 +1,9 @@
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct ListScaffoldsParams {
+    /// Words to search for in scaffold descriptions and plans; lists the most recent when omitted
     pub query: Option<String>,
+    /// Maximum number of scaffolds to return (defaults to 5)
     pub limit: Option<i64>,
+    /// Number of results to skip, for paging through older or lower-ranked scaffolds
+    pub offset: Option<i64>,
 }
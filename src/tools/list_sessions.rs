@@ -0,0 +1,8 @@
+/// Input parameters for the `list_sessions` tool.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ListSessionsParams {
+    /// Maximum number of sessions to return (defaults to 5)
+    pub limit: Option<i64>,
+    /// Number of sessions to skip, for paging back through older ones
+    pub offset: Option<i64>,
+}
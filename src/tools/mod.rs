@@ -1,21 +1,51 @@
+mod build_doc_index;
 mod check_crate_docs;
+mod diff_between;
+mod explain_error;
 mod get_changes_by_change_id;
+mod get_file_at;
 mod get_file_changes;
 mod get_man_page;
+mod get_review;
 mod get_scaffold;
+mod get_session;
+mod learning_report;
+mod list_dependencies;
 mod list_recent_change_ids;
+mod list_reviews;
 mod list_scaffolds;
+mod list_sessions;
+mod recent_diagnostics;
+mod review_changes;
 mod review_file;
+mod run_clippy;
 mod save_scaffold;
 mod scaffold;
+mod search_local_docs;
+mod usage_report;
 
+pub use build_doc_index::BuildDocIndexParams;
 pub use check_crate_docs::CheckCrateDocsParams;
+pub use diff_between::DiffBetweenParams;
+pub use explain_error::ExplainErrorParams;
 pub use get_changes_by_change_id::GetChangesByChangeIdParams;
+pub use get_file_at::GetFileAtParams;
 pub use get_file_changes::GetFileChangesParams;
 pub use get_man_page::GetManPageParams;
+pub use get_review::GetReviewParams;
 pub use get_scaffold::GetScaffoldParams;
+pub use get_session::GetSessionParams;
+pub use learning_report::LearningReportParams;
+pub use list_dependencies::ListDependenciesParams;
 pub use list_recent_change_ids::ListRecentChangesParams;
+pub use list_reviews::ListReviewsParams;
 pub use list_scaffolds::ListScaffoldsParams;
+pub use list_sessions::ListSessionsParams;
+pub use recent_diagnostics::RecentDiagnosticsParams;
+pub use review_changes::ReviewChangesParams;
 pub use review_file::ReviewFileParams;
+pub use run_clippy::RunClippyParams;
 pub use save_scaffold::SaveScaffoldParams;
 pub use scaffold::ScaffoldParams;
+pub use search_local_docs::SearchLocalDocsParams;
+pub use usage_report::UsageReportParams;
@@ -0,0 +1,14 @@
+/// Input parameters for the `recent_diagnostics` tool.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct RecentDiagnosticsParams {
+    /// Only include diagnostics in this file (absolute path, as the watcher records it)
+    pub file_path: Option<String>,
+    /// Only include this level, e.g. `error` or `warning`
+    pub level: Option<String>,
+    /// Only include compiler diagnostics (`check`) or clippy lints (`clippy`)
+    pub source: Option<String>,
+    /// How many days back to count repeat diagnostics over (defaults to 7, at most 3650)
+    pub days: Option<i64>,
+    /// Maximum number of recent diagnostics to list (defaults to 5)
+    pub limit: Option<i64>,
+}
@@ -0,0 +1,12 @@
+/// Input parameters for the `review_changes` tool.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ReviewChangesParams {
+    /// Review the hunks recorded under this change ID
+    pub change_id: Option<String>,
+    /// Review every hunk saved at or after this RFC 3339 time (ignored when change_id is set)
+    pub since: Option<String>,
+    /// End of the time range, RFC 3339 (defaults to now; requires since)
+    pub until: Option<String>,
+    /// Lines of surrounding code to include around each hunk (defaults to 5, at most 50)
+    pub context_lines: Option<usize>,
+}
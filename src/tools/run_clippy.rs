@@ -0,0 +1,6 @@
+/// Input parameters for the `run_clippy` tool.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct RunClippyParams {
+    /// Lint only the crate this file belongs to and list its findings first; lints the project when omitted
+    pub file_path: Option<String>,
+}
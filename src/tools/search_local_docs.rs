@@ -0,0 +1,14 @@
+/// Input parameters for the `search_local_docs` tool.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct SearchLocalDocsParams {
+    /// Item name or path to look for, e.g. `Deserialize` or `de::Visitor`; close misspellings still match
+    pub query: String,
+    /// Only search this crate
+    pub crate_name: Option<String>,
+    /// Only include this kind of item, e.g. `struct`, `trait`, `fn` or `macro`
+    pub kind: Option<String>,
+    /// `summary` (default) or `full`, as for `check_crate_docs`
+    pub detail: Option<String>,
+    /// Maximum number of other matches to list after the best one (defaults to 10)
+    pub limit: Option<i64>,
+}
@@ -0,0 +1,6 @@
+/// Input parameters for the `usage_report` tool.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct UsageReportParams {
+    /// How many days back to include (defaults to 30, at most 3650)
+    pub days: Option<i64>,
+}
@@ -0,0 +1,153 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::{
+    config::ModelPrice,
+    store::{UsageGrouping, UsageRow},
+};
+
+// list prices for the Anthropic models this server defaults to, matched by
+// prefix so dated model ids (e.g. `claude-haiku-4-5-20251001`) resolve too
+const BUILTIN_PRICES: &[(&str, ModelPrice)] = &[
+    (
+        "claude-opus-4-1",
+        ModelPrice {
+            input_per_mtok: 15.0,
+            output_per_mtok: 75.0,
+        },
+    ),
+    (
+        "claude-sonnet-4",
+        ModelPrice {
+            input_per_mtok: 3.0,
+            output_per_mtok: 15.0,
+        },
+    ),
+    (
+        "claude-haiku-4-5",
+        ModelPrice {
+            input_per_mtok: 1.0,
+            output_per_mtok: 5.0,
+        },
+    ),
+];
+
+/// Resolves a model name to a price: config entries first, then built-ins.
+pub struct Pricing {
+    overrides: HashMap<String, ModelPrice>,
+}
+
+impl Pricing {
+    pub fn new(overrides: HashMap<String, ModelPrice>) -> Self {
+        Self { overrides }
+    }
+
+    fn price(&self, model: &str) -> Option<ModelPrice> {
+        self.overrides.get(model).copied().or_else(|| {
+            BUILTIN_PRICES
+                .iter()
+                .find(|(prefix, _)| model.starts_with(prefix))
+                .map(|(_, price)| *price)
+        })
+    }
+
+    fn cost(&self, row: &UsageRow) -> Option<f64> {
+        self.price(&row.model).map(|p| {
+            (row.input_tokens as f64 * p.input_per_mtok
+                + row.output_tokens as f64 * p.output_per_mtok)
+                / 1_000_000.0
+        })
+    }
+}
+
+#[derive(Default)]
+struct Totals {
+    calls: i64,
+    input_tokens: i64,
+    output_tokens: i64,
+    latency_ms: i64,
+    cost: f64,
+}
+
+impl Totals {
+    fn add(&mut self, row: &UsageRow, cost: Option<f64>) {
+        self.calls += row.calls;
+        self.input_tokens += row.input_tokens;
+        self.output_tokens += row.output_tokens;
+        self.latency_ms += row.total_latency_ms;
+        self.cost += cost.unwrap_or(0.0);
+    }
+}
+
+// render_report - this turns per-(key, model) usage rows into a markdown
+// report with one table per grouping
+pub fn render_report(
+    days: i64,
+    sections: &[(UsageGrouping, Vec<UsageRow>)],
+    pricing: &Pricing,
+) -> String {
+    // every grouping covers the same calls, so any section gives the total
+    let mut total = Totals::default();
+    for row in sections
+        .first()
+        .map(|(_, rows)| rows.as_slice())
+        .unwrap_or_default()
+    {
+        total.add(row, pricing.cost(row));
+    }
+    if total.calls == 0 {
+        return format!("No API calls recorded in the last {days} day(s)");
+    }
+
+    let mut out = format!(
+        "# API usage — last {days} day(s)\n\n**Total**: {} call(s), {} input / {} output tokens, ~${:.4}\n\n",
+        total.calls, total.input_tokens, total.output_tokens, total.cost
+    );
+    let mut unpriced = BTreeSet::new();
+
+    for (grouping, rows) in sections {
+        let mut by_key: BTreeMap<&str, Totals> = BTreeMap::new();
+        for row in rows {
+            let cost = pricing.cost(row);
+            if cost.is_none() {
+                unpriced.insert(row.model.as_str());
+            }
+            by_key.entry(&row.key).or_default().add(row, cost);
+        }
+
+        let (title, column) = match grouping {
+            UsageGrouping::Day => ("By day", "Day"),
+            UsageGrouping::Tool => ("By tool", "Tool"),
+            UsageGrouping::File => ("By file", "File"),
+        };
+
+        let mut entries: Vec<_> = by_key.into_iter().collect();
+        match grouping {
+            UsageGrouping::Day => entries.reverse(),
+            _ => entries.sort_by(|a, b| b.1.cost.total_cmp(&a.1.cost).then(a.0.cmp(b.0))),
+        }
+
+        out.push_str(&format!(
+            "## {title}\n\n| {column} | Calls | Input tokens | Output tokens | Avg latency | Est. cost |\n|---|---:|---:|---:|---:|---:|\n"
+        ));
+        for (key, t) in entries {
+            out.push_str(&format!(
+                "| `{key}` | {} | {} | {} | {} ms | ${:.4} |\n",
+                t.calls,
+                t.input_tokens,
+                t.output_tokens,
+                t.latency_ms / t.calls.max(1),
+                t.cost
+            ));
+        }
+        out.push('\n');
+    }
+
+    if !unpriced.is_empty() {
+        out.push_str(&format!(
+            "_No price configured for: {} — counted as $0. Add them under `[pricing]` in config.toml._\n",
+            unpriced.into_iter().collect::<Vec<_>>().join(", ")
+        ));
+    }
+
+    out
+}
@@ -1,108 +1,521 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     path::{Path, PathBuf},
     process::Command,
-    sync::{Arc, Mutex},
+    sync::{
+        mpsc::{RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
     thread::JoinHandle,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
-use notify_debouncer_mini::{new_debouncer, notify::RecursiveMode, DebouncedEventKind};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::{
+    gitignore::{Gitignore, GitignoreBuilder},
+    WalkBuilder,
+};
+use notify_debouncer_mini::{
+    new_debouncer,
+    notify::{RecursiveMode, Watcher},
+    DebouncedEventKind,
+};
 use similar::{ChangeTag, TextDiff};
 
-use crate::store::{FileChangeRecord, TutorStore};
+use crate::{
+    cargo::{CargoChecker, CheckRequest},
+    config::{CargoCheckConfig, WatcherConfig},
+    history, manifest,
+    store::{DependencyChangeRecord, FileChangeRecord, SnapshotRecord, TutorStore},
+};
+
+const IGNORE_FILES: [&str; 2] = [".gitignore", ".ignore"];
+// how alike a deleted and a new file must be (0.0-1.0) to count as a rename
+const RENAME_SIMILARITY: f32 = 0.6;
+// the debouncer reports the two halves of a move separately, so a deleted
+// file waits this long for its new path to show up before it counts as deleted
+const RENAME_WINDOW: Duration = Duration::from_secs(2);
+// saves of a file between full snapshots; bounds how many hunks get replayed
+const SNAPSHOT_INTERVAL: i64 = 20;
 
 pub struct FileWatcher {}
 
 impl FileWatcher {
-    pub fn spawn(store: Arc<Mutex<TutorStore>>) -> JoinHandle<()> {
+    pub fn spawn(
+        store: Arc<Mutex<TutorStore>>,
+        config: WatcherConfig,
+        cargo_check: CargoCheckConfig,
+    ) -> JoinHandle<()> {
         std::thread::spawn(move || {
             let Some(root) = detect_project_root() else {
                 tracing::warn!("could not detect project root — file watcher will not run");
                 return;
             };
 
-            let mut state = WatcherState::new(&root, store);
+            let checker = if !cargo_check.enabled {
+                None
+            } else if root.join("Cargo.toml").exists() {
+                Some(CargoChecker::spawn(
+                    root.clone(),
+                    cargo_check,
+                    Arc::clone(&store),
+                ))
+            } else {
+                tracing::warn!("no Cargo.toml at the project root — cargo check on save is off");
+                None
+            };
 
             let (tx, rx) = std::sync::mpsc::channel();
             let mut debounder =
                 new_debouncer(Duration::from_millis(500), tx).expect("failed to create debouncer");
 
-            debounder
-                .watcher()
-                .watch(&root, RecursiveMode::Recursive)
-                .expect("failed to watch project root");
+            // only directories that survive the ignore rules get a watch, so
+            // target/ and friends never cost us an inotify watch or an event
+            let mut state = WatcherState::new(&root, &config, store, checker);
+            let tracked = state.watch_tree(&root, debounder.watcher());
+            state.seed(tracked);
+            tracing::debug!(
+                files = state.last_seen.len(),
+                dirs = state.watched.len(),
+                "file watcher ready"
+            );
+
+            loop {
+                // wake up at least once per rename window to flush deletes
+                // that never got paired with a new file
+                let result = match rx.recv_timeout(RENAME_WINDOW) {
+                    Ok(result) => Some(result),
+                    Err(RecvTimeoutError::Timeout) => None,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                };
 
-            for result in rx {
                 match result {
-                    Ok(events) => {
-                        for event in events {
-                            if event.path.extension().and_then(|e| e.to_str()) == Some("rs") {
-                                match event.kind {
-                                    DebouncedEventKind::Any | DebouncedEventKind::AnyContinuous => {
-                                        if event.path.exists() {
-                                            state.process_event(&event.path);
-                                        } else {
-                                            state.last_seen.remove(&event.path);
-                                        }
-                                    }
-                                    _ => {}
-                                }
-                            }
-                        }
+                    Some(Ok(events)) => {
+                        let paths: Vec<PathBuf> = events
+                            .into_iter()
+                            .filter(|event| {
+                                matches!(
+                                    event.kind,
+                                    DebouncedEventKind::Any | DebouncedEventKind::AnyContinuous
+                                )
+                            })
+                            .map(|event| event.path)
+                            .collect();
+                        state.handle_events(paths, debounder.watcher());
                     }
-                    Err(e) => {
+                    Some(Err(e)) => {
                         tracing::error!("file watcher error: {e}");
                     }
+                    None => {}
                 }
+
+                state.flush_deletes();
             }
             let _ = debounder;
         })
     }
 }
 
+/// Decides which paths are part of the project's history: files matching a
+/// `[watcher] include` pattern, minus anything matched by a `.gitignore` or
+/// `.ignore` in the tree, a `[watcher] exclude` pattern or a hidden directory.
+struct PathFilter {
+    root: PathBuf,
+    // patterns with a `/` match the path relative to the root, others the file name
+    include_paths: GlobSet,
+    include_names: GlobSet,
+    exclude: Gitignore,
+    // keyed by the ignore file itself, since .gitignore and .ignore can share a directory
+    ignore_files: HashMap<PathBuf, Gitignore>,
+}
+
+impl PathFilter {
+    fn new(root: &Path, include: &[String], exclude: &[String]) -> Self {
+        let mut paths = GlobSetBuilder::new();
+        let mut names = GlobSetBuilder::new();
+        for pattern in include {
+            match Glob::new(pattern) {
+                Ok(glob) if pattern.contains('/') => {
+                    paths.add(glob);
+                }
+                Ok(glob) => {
+                    names.add(glob);
+                }
+                Err(e) => tracing::warn!("skipping invalid watcher include `{pattern}`: {e}"),
+            }
+        }
+        let build = |builder: GlobSetBuilder| {
+            builder.build().unwrap_or_else(|e| {
+                tracing::warn!("failed to build watcher includes: {e}");
+                GlobSet::empty()
+            })
+        };
+
+        let mut builder = GitignoreBuilder::new(root);
+        for pattern in exclude {
+            if let Err(e) = builder.add_line(None, pattern) {
+                tracing::warn!("skipping invalid watcher exclude `{pattern}`: {e}");
+            }
+        }
+        let exclude = builder.build().unwrap_or_else(|e| {
+            tracing::warn!("failed to build watcher excludes: {e}");
+            Gitignore::empty()
+        });
+
+        Self {
+            root: root.to_path_buf(),
+            include_paths: build(paths),
+            include_names: build(names),
+            exclude,
+            ignore_files: HashMap::new(),
+        }
+    }
+
+    fn is_tracked(&self, path: &Path) -> bool {
+        let name_matches = path
+            .file_name()
+            .is_some_and(|name| self.include_names.is_match(name));
+        name_matches
+            || path
+                .strip_prefix(&self.root)
+                .is_ok_and(|relative| self.include_paths.is_match(relative))
+    }
+
+    fn is_ignore_file(path: &Path) -> bool {
+        path.file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| IGNORE_FILES.contains(&n))
+    }
+
+    // load_ignore_file - this (re)reads an ignore file after it is found or edited
+    fn load_ignore_file(&mut self, path: &Path) {
+        if !path.exists() {
+            self.ignore_files.remove(path);
+            return;
+        }
+
+        let (gitignore, error) = Gitignore::new(path);
+        if let Some(e) = error {
+            tracing::warn!("problem reading {}: {e}", path.display());
+        }
+        self.ignore_files.insert(path.to_path_buf(), gitignore);
+    }
+
+    fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return true;
+        };
+        let hidden = relative
+            .components()
+            .any(|c| c.as_os_str().to_str().is_some_and(|s| s.starts_with('.')));
+        if hidden
+            || self
+                .exclude
+                .matched_path_or_any_parents(path, is_dir)
+                .is_ignore()
+        {
+            return true;
+        }
+
+        self.ignore_files.values().any(|gitignore| {
+            path.starts_with(gitignore.path())
+                && path != gitignore.path()
+                && gitignore
+                    .matched_path_or_any_parents(path, is_dir)
+                    .is_ignore()
+        })
+    }
+}
+
+/// Directories and tracked files under one directory that passed the ignore rules.
+#[derive(Default)]
+struct Scan {
+    dirs: Vec<PathBuf>,
+    tracked_files: Vec<PathBuf>,
+    ignore_files: Vec<PathBuf>,
+}
+
+// scan - this walks `dir` with the `ignore` crate so .gitignore, .ignore and
+// .git/info/exclude prune whole directories before we ever read them
+fn scan(dir: &Path, filter: &PathFilter) -> Scan {
+    let exclude = filter.exclude.clone();
+    let walker = WalkBuilder::new(dir)
+        // hidden entries are filtered below, except the ignore files we need to load
+        .hidden(false)
+        // .gitignore applies outside git repos too, as it does to events
+        .require_git(false)
+        .filter_entry(move |entry| {
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            let hidden = entry
+                .file_name()
+                .to_str()
+                .is_some_and(|n| n.starts_with('.'));
+            if hidden && (is_dir || !PathFilter::is_ignore_file(entry.path())) {
+                return false;
+            }
+            !exclude
+                .matched_path_or_any_parents(entry.path(), is_dir)
+                .is_ignore()
+        })
+        .build();
+
+    let mut scan = Scan::default();
+    for entry in walker.filter_map(|e| e.ok()) {
+        let path = entry.into_path();
+        if path.is_dir() {
+            scan.dirs.push(path);
+        } else if PathFilter::is_ignore_file(&path) {
+            scan.ignore_files.push(path);
+        } else if filter.is_tracked(&path) {
+            scan.tracked_files.push(path);
+        }
+    }
+
+    scan
+}
+
 struct WatcherState {
     last_seen: HashMap<PathBuf, String>,
+    // deleted files that may still turn out to be renames
+    pending_deletes: Vec<PendingDelete>,
+    watched: HashSet<PathBuf>,
+    filter: PathFilter,
+    // the session saves are currently going into, and when it last saw one
+    session: Option<(i64, chrono::DateTime<chrono::Utc>)>,
+    session_gap: chrono::Duration,
+    // set when `[cargo_check]` is enabled
+    checker: Option<Sender<CheckRequest>>,
     db: Arc<Mutex<TutorStore>>,
 }
 
 impl WatcherState {
-    fn new(root: &Path, db: Arc<Mutex<TutorStore>>) -> Self {
-        let mut last_seen = HashMap::new();
+    fn new(
+        root: &Path,
+        config: &WatcherConfig,
+        db: Arc<Mutex<TutorStore>>,
+        checker: Option<Sender<CheckRequest>>,
+    ) -> Self {
+        // pick up where the last run left off, so a quick restart doesn't
+        // split a session in two
+        let session = match db.lock().expect("store lock poisoned").latest_session() {
+            Ok(latest) => latest.map(|s| (s.id, s.ended_at)),
+            Err(e) => {
+                tracing::error!("failed to load the latest session: {e}");
+                None
+            }
+        };
 
-        // walk the project and seed the last seen map
-        for entry in walkdir::WalkDir::new(root)
-            .into_iter()
-            .filter_map(|e| e.ok())
-            .filter(|e| e.path().extension().and_then(|x| x.to_str()) == Some("rs"))
-        {
-            if let Ok(contents) = std::fs::read_to_string(entry.path()) {
-                last_seen.insert(entry.path().to_path_buf(), contents);
+        Self {
+            last_seen: HashMap::new(),
+            pending_deletes: Vec::new(),
+            watched: HashSet::new(),
+            filter: PathFilter::new(root, &config.include, &config.exclude),
+            session,
+            session_gap: chrono::Duration::minutes(config.session_gap_mins),
+            checker,
+            db,
+        }
+    }
+
+    // watch_tree - this watches every non-ignored directory under `dir` and
+    // returns the tracked files in it
+    fn watch_tree(&mut self, dir: &Path, watcher: &mut dyn Watcher) -> Vec<PathBuf> {
+        let scan = scan(dir, &self.filter);
+
+        for path in &scan.ignore_files {
+            self.filter.load_ignore_file(path);
+        }
+
+        for path in scan.dirs {
+            if self.watched.contains(&path) {
+                continue;
+            }
+            match watcher.watch(&path, RecursiveMode::NonRecursive) {
+                Ok(()) => {
+                    self.watched.insert(path);
+                }
+                Err(e) => tracing::warn!("failed to watch {}: {e}", path.display()),
             }
         }
 
-        Self { last_seen, db }
+        scan.tracked_files
     }
 
-    fn process_event(&mut self, path: &Path) {
-        let contents = match std::fs::read_to_string(path) {
-            Ok(contents) => contents,
-            Err(e) => {
-                tracing::error!("failed to read file: {e}");
-                return;
+    // handle_events - this sorts one debounced batch into edits, creates and
+    // deletes, then pairs each recently deleted file with the most similar new
+    // file to record it as a rename instead of a delete plus a create
+    fn handle_events(&mut self, paths: Vec<PathBuf>, watcher: &mut dyn Watcher) {
+        let mut touched = Vec::new();
+
+        for path in paths {
+            if PathFilter::is_ignore_file(&path) {
+                self.filter.load_ignore_file(&path);
+                continue;
             }
-        };
 
-        let empty = String::new();
-        let old = self.last_seen.get(path).unwrap_or(&empty);
+            if !path.exists() {
+                // a removed directory takes its watch and its files with it
+                self.watched.remove(&path);
+                let gone: Vec<PathBuf> = self
+                    .last_seen
+                    .keys()
+                    .filter(|seen| seen.starts_with(&path))
+                    .cloned()
+                    .collect();
+                for seen in gone {
+                    if let Some(contents) = self.last_seen.remove(&seen) {
+                        self.pending_deletes.push(PendingDelete {
+                            path: seen,
+                            contents,
+                            at: Instant::now(),
+                        });
+                    }
+                }
+                continue;
+            }
 
-        if old == &contents {
-            return;
+            let is_dir = path.is_dir();
+            if self.filter.is_ignored(&path, is_dir) {
+                continue;
+            }
+
+            if is_dir {
+                if !self.watched.contains(&path) {
+                    touched.extend(self.watch_tree(&path, watcher));
+                }
+            } else if self.filter.is_tracked(&path) {
+                touched.push(path);
+            }
         }
 
-        let hunks = extract_hunks(old, &contents);
+        touched.sort();
+        touched.dedup();
+
+        let mut created = Vec::new();
+        for path in touched {
+            let contents = match std::fs::read_to_string(&path) {
+                Ok(contents) => contents,
+                Err(e) => {
+                    tracing::error!("failed to read file: {e}");
+                    continue;
+                }
+            };
+            // editors that save by deleting and recreating the file look like
+            // a delete followed by a create of the same path
+            let previous = self.last_seen.remove(&path).or_else(|| {
+                let i = self.pending_deletes.iter().position(|p| p.path == path)?;
+                Some(self.pending_deletes.swap_remove(i).contents)
+            });
+            match previous {
+                Some(old) => self.record_change(ChangeKind::Modify, &path, None, &old, contents),
+                None => created.push((path, contents)),
+            }
+        }
+
+        for pending in std::mem::take(&mut self.pending_deletes) {
+            let best = created
+                .iter()
+                .enumerate()
+                .map(|(i, (_, new))| (i, TextDiff::from_lines(&pending.contents, new).ratio()))
+                .filter(|(_, ratio)| *ratio >= RENAME_SIMILARITY)
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+
+            match best {
+                Some((i, _)) => {
+                    let (path, contents) = created.swap_remove(i);
+                    self.record_change(
+                        ChangeKind::Rename,
+                        &path,
+                        Some(&pending.path),
+                        &pending.contents,
+                        contents,
+                    );
+                }
+                None => self.pending_deletes.push(pending),
+            }
+        }
+
+        for (path, contents) in created {
+            self.record_change(ChangeKind::Create, &path, None, "", contents);
+        }
+    }
+
+    // flush_deletes - this records deletes that waited out the rename window
+    fn flush_deletes(&mut self) {
+        let (expired, waiting) = std::mem::take(&mut self.pending_deletes)
+            .into_iter()
+            .partition(|pending| pending.at.elapsed() >= RENAME_WINDOW);
+        self.pending_deletes = waiting;
+
+        for pending in expired {
+            self.record_change(
+                ChangeKind::Delete,
+                &pending.path,
+                None,
+                &pending.contents,
+                String::new(),
+            );
+        }
+    }
+
+    // record_change - this stores the hunks between `old` and `contents` under
+    // one change id and remembers `contents` as the file's latest version
+    fn record_change(
+        &mut self,
+        kind: ChangeKind,
+        path: &Path,
+        old_path: Option<&Path>,
+        old: &str,
+        contents: String,
+    ) {
+        if kind == ChangeKind::Modify && old == contents {
+            self.last_seen.insert(path.to_path_buf(), contents);
+            return;
+        }
 
         let change_id = uuid::Uuid::new_v4().to_string();
+        let changed_at = chrono::Utc::now();
+
+        // manifests and lockfiles are recorded as dependency changes; a
+        // Cargo.toml edit that touches no dependency falls through to hunks.
+        // Their history is kept as snapshots since there are no hunks to replay
+        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if matches!(kind, ChangeKind::Modify | ChangeKind::Create) {
+            if let Some(dependency_changes) = manifest::dependency_diff(file_name, old, &contents) {
+                if !dependency_changes.is_empty() || file_name == "Cargo.lock" {
+                    let session_id = self.session_for(changed_at, 0, 0);
+                    self.save_dependency_changes(
+                        path,
+                        &change_id,
+                        changed_at,
+                        session_id,
+                        dependency_changes,
+                    );
+                    self.save_snapshot(
+                        path,
+                        Some(&change_id),
+                        "dependencies",
+                        &contents,
+                        changed_at,
+                    );
+                    self.request_check(&change_id, session_id);
+                    self.last_seen.insert(path.to_path_buf(), contents);
+                    return;
+                }
+            }
+        }
+
+        let mut hunks = extract_hunks(old, &contents);
+        if hunks.is_empty() {
+            // an unchanged rename still needs a row to show up in the history
+            hunks.push(HunkData::default());
+        }
+
+        let lines_added = hunks.iter().map(|h| h.lines_added).sum();
+        let lines_removed = hunks.iter().map(|h| h.lines_removed).sum();
+        let session_id = self.session_for(changed_at, lines_added, lines_removed);
+
+        let hash = history::content_hash(&contents);
         for hunk in hunks {
             let record = FileChangeRecord {
                 id: 0,
@@ -115,7 +528,14 @@ impl WatcherState {
                 new_count: hunk.new_count,
                 before_lines: hunk.before_lines,
                 after_lines: hunk.after_lines,
-                changed_at: chrono::Utc::now(),
+                changed_at,
+                kind: kind.as_str().to_string(),
+                old_path: old_path.map(|p| p.to_string_lossy().into_owned()),
+                new_text: Some(hunk.new_text),
+                content_hash: Some(hash.clone()),
+                lines_added: hunk.lines_added,
+                lines_removed: hunk.lines_removed,
+                session_id,
             };
 
             self.db
@@ -125,10 +545,185 @@ impl WatcherState {
                 .expect("failed to save file change");
         }
 
-        self.last_seen.insert(path.to_path_buf(), contents);
+        // creates and renames start a new line of history at this path; other
+        // saves get a snapshot every so often to keep reconstruction short
+        let reason = match kind {
+            ChangeKind::Delete => None,
+            ChangeKind::Create => Some("create"),
+            ChangeKind::Rename => Some("rename"),
+            ChangeKind::Modify => {
+                let saves = self
+                    .db
+                    .lock()
+                    .expect("store lock poisoned")
+                    .saves_since_snapshot(&path.to_string_lossy())
+                    .unwrap_or(0);
+                (saves >= SNAPSHOT_INTERVAL).then_some("periodic")
+            }
+        };
+        if let Some(reason) = reason {
+            self.save_snapshot(path, Some(&change_id), reason, &contents, changed_at);
+        }
+
+        self.request_check(&change_id, session_id);
+        if kind != ChangeKind::Delete {
+            self.last_seen.insert(path.to_path_buf(), contents);
+        }
+    }
+
+    // request_check - this queues a background `cargo check` for a save
+    fn request_check(&self, change_id: &str, session_id: Option<i64>) {
+        if let Some(checker) = &self.checker {
+            let request = CheckRequest {
+                change_id: change_id.to_string(),
+                session_id,
+            };
+            if checker.send(request).is_err() {
+                tracing::warn!("cargo check thread is gone — diagnostics won't be recorded");
+            }
+        }
+    }
+
+    // session_for - this returns the session a save at `at` belongs to and
+    // adds its churn there, starting a new session when the last save was
+    // more than the configured gap ago
+    fn session_for(
+        &mut self,
+        at: chrono::DateTime<chrono::Utc>,
+        lines_added: i64,
+        lines_removed: i64,
+    ) -> Option<i64> {
+        let store = self.db.lock().expect("store lock poisoned");
+        let saved = match self.session {
+            Some((id, last_save)) if at - last_save <= self.session_gap => store
+                .record_session_activity(id, at, lines_added, lines_removed)
+                .map(|()| id),
+            _ => store.start_session(at, lines_added, lines_removed),
+        };
+
+        match saved {
+            Ok(id) => {
+                self.session = Some((id, at));
+                Some(id)
+            }
+            Err(e) => {
+                tracing::error!("failed to record session: {e}");
+                None
+            }
+        }
+    }
+
+    // seed - this remembers the tracked files found at startup, and takes a
+    // base snapshot of any whose contents don't match the last recorded state
+    // (new to us, or edited while the server wasn't running)
+    fn seed(&mut self, paths: Vec<PathBuf>) {
+        let known = self
+            .db
+            .lock()
+            .expect("store lock poisoned")
+            .latest_content_hashes()
+            .unwrap_or_else(|e| {
+                tracing::error!("failed to load file history: {e}");
+                HashMap::new()
+            });
+        let now = chrono::Utc::now();
+
+        for path in paths {
+            let Ok(contents) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let hash = history::content_hash(&contents);
+            if known.get(path.to_string_lossy().as_ref()) != Some(&hash) {
+                self.save_snapshot(&path, None, "base", &contents, now);
+            }
+            self.last_seen.insert(path, contents);
+        }
+    }
+
+    fn save_snapshot(
+        &self,
+        path: &Path,
+        change_id: Option<&str>,
+        reason: &str,
+        contents: &str,
+        created_at: chrono::DateTime<chrono::Utc>,
+    ) {
+        let snapshot = SnapshotRecord {
+            file_path: path.to_string_lossy().into_owned(),
+            change_id: change_id.map(String::from),
+            reason: reason.to_string(),
+            content: contents.to_string(),
+            content_hash: history::content_hash(contents),
+            created_at,
+        };
+
+        if let Err(e) = self
+            .db
+            .lock()
+            .expect("store lock poisoned")
+            .save_snapshot(&snapshot)
+        {
+            tracing::error!("failed to save snapshot: {e}");
+        }
+    }
+
+    fn save_dependency_changes(
+        &self,
+        path: &Path,
+        change_id: &str,
+        changed_at: chrono::DateTime<chrono::Utc>,
+        session_id: Option<i64>,
+        changes: Vec<manifest::DependencyChange>,
+    ) {
+        let store = self.db.lock().expect("store lock poisoned");
+
+        for change in changes {
+            let record = DependencyChangeRecord {
+                change_id: change_id.to_string(),
+                file_path: path.to_string_lossy().into_owned(),
+                crate_name: change.crate_name,
+                section: change.section,
+                kind: change.kind.as_str().to_string(),
+                old_version: change.old_version,
+                new_version: change.new_version,
+                changed_at,
+                session_id,
+            };
+
+            if let Err(e) = store.save_dependency_change(&record) {
+                tracing::error!("failed to save dependency change: {e}");
+            }
+        }
+    }
+}
+
+struct PendingDelete {
+    path: PathBuf,
+    contents: String,
+    at: Instant,
+}
+
+/// What happened to a file in one save event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChangeKind {
+    Create,
+    Modify,
+    Delete,
+    Rename,
+}
+
+impl ChangeKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            ChangeKind::Create => "create",
+            ChangeKind::Modify => "modify",
+            ChangeKind::Delete => "delete",
+            ChangeKind::Rename => "rename",
+        }
     }
 }
 
+#[derive(Default)]
 struct HunkData {
     idx: usize,
     old_start: i64,
@@ -137,6 +732,9 @@ struct HunkData {
     new_count: i64,
     before_lines: String,
     after_lines: String,
+    new_text: String,
+    lines_added: i64,
+    lines_removed: i64,
 }
 
 fn extract_hunks(old: &str, new: &str) -> Vec<HunkData> {
@@ -162,19 +760,22 @@ fn extract_hunks(old: &str, new: &str) -> Vec<HunkData> {
 
         hunks.push(HunkData {
             idx,
+            lines_added: after_lines.len() as i64,
+            lines_removed: before_lines.len() as i64,
             old_start,
             old_count: old_end - old_start,
             new_start,
             new_count: new_end - new_start,
             before_lines: before_lines.join("\n"),
             after_lines: after_lines.join("\n"),
+            new_text: diff.new_slices()[new_start as usize..new_end as usize].concat(),
         });
     }
 
     hunks
 }
 
-fn detect_project_root() -> Option<PathBuf> {
+pub(crate) fn detect_project_root() -> Option<PathBuf> {
     Command::new("git")
         .args(["rev-parse", "--show-toplevel"])
         .output()